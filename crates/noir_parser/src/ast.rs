@@ -0,0 +1,430 @@
+use std::fmt;
+
+/// A byte range into the source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    pub fn merge(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ident {
+    pub name: String,
+    pub span: Span,
+}
+
+impl fmt::Display for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub segments: Vec<Ident>,
+}
+
+impl Path {
+    pub fn span(&self) -> Span {
+        let first = self.segments.first().map(|s| s.span).unwrap_or_default();
+        let last = self.segments.last().map(|s| s.span).unwrap_or_default();
+        first.merge(last)
+    }
+
+    /// Returns the identifier if the path has a single segment.
+    pub fn as_ident(&self) -> Option<&Ident> {
+        match self.segments.as_slice() {
+            [ident] => Some(ident),
+            _ => None,
+        }
+    }
+
+    pub fn last_segment(&self) -> &Ident {
+        self.segments
+            .last()
+            .expect("paths have at least one segment")
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segments: Vec<&str> = self.segments.iter().map(|s| s.name.as_str()).collect();
+        write!(f, "{}", segments.join("::"))
+    }
+}
+
+/// The result of parsing a single Noir source file.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedModule {
+    pub imports: Vec<ImportStatement>,
+    pub functions: Vec<FunctionDefinition>,
+    pub types: Vec<NoirStruct>,
+    pub globals: Vec<GlobalDefinition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportStatement {
+    pub path: Path,
+    pub alias: Option<Ident>,
+}
+
+/// An outer attribute such as `#[test]` or `#[foo(bar)]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    pub name: String,
+    pub arguments: Vec<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AbiFEType {
+    Public,
+    Private,
+}
+
+/// Whether the return values of `main` must be distinct witnesses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Distinctness {
+    Distinct,
+    DuplicationAllowed,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionDefinition {
+    pub name: Ident,
+    pub attributes: Vec<Attribute>,
+    pub parameters: Vec<(Pattern, UnresolvedType, AbiFEType)>,
+    pub body: BlockExpression,
+    pub span: Span,
+    pub return_type: UnresolvedType,
+    pub return_visibility: AbiFEType,
+    pub return_distinctness: Distinctness,
+}
+
+#[derive(Debug, Clone)]
+pub struct NoirStruct {
+    pub name: Ident,
+    pub attributes: Vec<Attribute>,
+    pub fields: Vec<(Ident, UnresolvedType)>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct GlobalDefinition {
+    pub name: Ident,
+    pub attributes: Vec<Attribute>,
+    pub r#type: Option<UnresolvedType>,
+    pub expression: Expression,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signedness {
+    Unsigned,
+    Signed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnresolvedType {
+    FieldElement,
+    /// `[T; N]`, or a slice `[T]` when there is no length.
+    Array(Option<Box<Expression>>, Box<UnresolvedType>),
+    Integer(Signedness, u32),
+    Bool,
+    Unit,
+    Tuple(Vec<UnresolvedType>),
+    Named(Path),
+    Error,
+}
+
+impl fmt::Display for UnresolvedType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnresolvedType::FieldElement => write!(f, "Field"),
+            UnresolvedType::Array(None, element) => write!(f, "[{element}]"),
+            UnresolvedType::Array(Some(length), element) => match &length.kind {
+                ExpressionKind::Literal(Literal::Integer(length, _)) => {
+                    write!(f, "[{element}; {length}]")
+                }
+                _ => write!(f, "[{element}; _]"),
+            },
+            UnresolvedType::Integer(Signedness::Unsigned, bits) => write!(f, "u{bits}"),
+            UnresolvedType::Integer(Signedness::Signed, bits) => write!(f, "i{bits}"),
+            UnresolvedType::Bool => write!(f, "bool"),
+            UnresolvedType::Unit => write!(f, "()"),
+            UnresolvedType::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(ToString::to_string).collect();
+                write!(f, "({})", elements.join(", "))
+            }
+            UnresolvedType::Named(path) => write!(f, "{path}"),
+            UnresolvedType::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    Identifier(Ident),
+    Mutable(Box<Pattern>, Span),
+    Tuple(Vec<Pattern>, Span),
+    Struct(Path, Vec<(Ident, Pattern)>, Span),
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Identifier(ident) => ident.span,
+            Pattern::Mutable(_, span) | Pattern::Tuple(_, span) | Pattern::Struct(_, _, span) => {
+                *span
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockExpression(pub Vec<Statement>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    Let(LetStatement),
+    Constrain(ConstrainStatement),
+    /// An expression in tail position, whose value is the value of the block.
+    Expression(Expression),
+    /// An expression followed by a semicolon.
+    Semi(Expression),
+    Assign(AssignStatement),
+    Error,
+}
+
+impl Statement {
+    /// The source range of the statement, unless it failed to parse.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Statement::Let(let_statement) => Some(
+                let_statement
+                    .pattern
+                    .span()
+                    .merge(let_statement.expression.span),
+            ),
+            Statement::Constrain(ConstrainStatement(expression))
+            | Statement::Expression(expression)
+            | Statement::Semi(expression) => Some(expression.span),
+            Statement::Assign(assign_statement) => Some(assign_statement.expression.span),
+            Statement::Error => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LetStatement {
+    pub pattern: Pattern,
+    pub r#type: Option<UnresolvedType>,
+    pub expression: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstrainStatement(pub Expression);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignStatement {
+    pub lvalue: LValue,
+    pub expression: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LValue {
+    Ident(Ident),
+    MemberAccess {
+        object: Box<LValue>,
+        field_name: Ident,
+    },
+    Index {
+        array: Box<LValue>,
+        index: Expression,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Expression { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpressionKind {
+    Literal(Literal),
+    Block(BlockExpression),
+    Prefix(Box<PrefixExpression>),
+    Index(Box<IndexExpression>),
+    Call(Box<CallExpression>),
+    MethodCall(Box<MethodCallExpression>),
+    Constructor(Box<ConstructorExpression>),
+    MemberAccess(Box<MemberAccessExpression>),
+    Cast(Box<CastExpression>),
+    Infix(Box<InfixExpression>),
+    If(Box<IfExpression>),
+    For(Box<ForExpression>),
+    Tuple(Vec<Expression>),
+    Path(Path),
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Literal {
+    Array(ArrayLiteral),
+    Bool(bool),
+    /// An integer, with its type if it was written with a suffix like `2u32`.
+    Integer(u128, Option<UnresolvedType>),
+    Str(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArrayLiteral {
+    Standard(Vec<Expression>),
+    Repeated {
+        repeated_element: Box<Expression>,
+        length: Box<Expression>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnaryOp {
+    Minus,
+    Not,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixExpression {
+    pub operator: UnaryOp,
+    pub rhs: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexExpression {
+    pub collection: Expression,
+    pub index: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallExpression {
+    pub func: Box<Expression>,
+    pub arguments: Vec<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodCallExpression {
+    pub object: Expression,
+    pub method_name: Ident,
+    pub arguments: Vec<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstructorExpression {
+    pub type_name: Path,
+    pub fields: Vec<(Ident, Expression)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberAccessExpression {
+    pub lhs: Expression,
+    pub rhs: Ident,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CastExpression {
+    pub lhs: Expression,
+    pub r#type: UnresolvedType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOpKind {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+    Xor,
+    ShiftRight,
+    ShiftLeft,
+    Modulo,
+}
+
+impl BinaryOpKind {
+    pub fn is_comparator(self) -> bool {
+        matches!(
+            self,
+            BinaryOpKind::Equal
+                | BinaryOpKind::NotEqual
+                | BinaryOpKind::Less
+                | BinaryOpKind::LessEqual
+                | BinaryOpKind::Greater
+                | BinaryOpKind::GreaterEqual
+        )
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BinaryOpKind::Add => "+",
+            BinaryOpKind::Subtract => "-",
+            BinaryOpKind::Multiply => "*",
+            BinaryOpKind::Divide => "/",
+            BinaryOpKind::Equal => "==",
+            BinaryOpKind::NotEqual => "!=",
+            BinaryOpKind::Less => "<",
+            BinaryOpKind::LessEqual => "<=",
+            BinaryOpKind::Greater => ">",
+            BinaryOpKind::GreaterEqual => ">=",
+            BinaryOpKind::And => "&",
+            BinaryOpKind::Or => "|",
+            BinaryOpKind::Xor => "^",
+            BinaryOpKind::ShiftRight => ">>",
+            BinaryOpKind::ShiftLeft => "<<",
+            BinaryOpKind::Modulo => "%",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfixExpression {
+    pub lhs: Expression,
+    pub operator: BinaryOpKind,
+    pub rhs: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IfExpression {
+    pub condition: Expression,
+    pub consequence: Expression,
+    pub alternative: Option<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForExpression {
+    pub identifier: Ident,
+    pub start_range: Expression,
+    pub end_range: Expression,
+    pub block: Expression,
+}
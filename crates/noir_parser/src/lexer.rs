@@ -0,0 +1,316 @@
+use crate::{ast::Span, parser::ParserError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    Ident(String),
+    Int(u128),
+    /// An integer literal with a type suffix, such as `2u32`.
+    SuffixedInt(u128, String),
+    Str(String),
+    Keyword(Keyword),
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Semicolon,
+    Colon,
+    DoubleColon,
+    Dot,
+    DoubleDot,
+    Arrow,
+    Pound,
+    Assign,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Caret,
+    Ampersand,
+    Pipe,
+    Bang,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    ShiftLeft,
+    ShiftRight,
+    Eof,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Keyword {
+    As,
+    Constrain,
+    Distinct,
+    Else,
+    False,
+    Fn,
+    For,
+    Global,
+    If,
+    In,
+    Let,
+    Mut,
+    Pub,
+    Struct,
+    True,
+    Use,
+}
+
+impl Keyword {
+    fn lookup(word: &str) -> Option<Keyword> {
+        let keyword = match word {
+            "as" => Keyword::As,
+            "constrain" => Keyword::Constrain,
+            "distinct" => Keyword::Distinct,
+            "else" => Keyword::Else,
+            "false" => Keyword::False,
+            "fn" => Keyword::Fn,
+            "for" => Keyword::For,
+            "global" => Keyword::Global,
+            "if" => Keyword::If,
+            "in" => Keyword::In,
+            "let" => Keyword::Let,
+            "mut" => Keyword::Mut,
+            "pub" => Keyword::Pub,
+            "struct" => Keyword::Struct,
+            "true" => Keyword::True,
+            "use" => Keyword::Use,
+            _ => return None,
+        };
+        Some(keyword)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+pub(crate) fn tokenize(source: &str) -> Result<Vec<SpannedToken>, ParserError> {
+    let mut lexer = Lexer {
+        source: source.as_bytes(),
+        position: 0,
+    };
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next_token()?;
+        let is_eof = token.token == Token::Eof;
+        tokens.push(token);
+        if is_eof {
+            return Ok(tokens);
+        }
+    }
+}
+
+struct Lexer<'a> {
+    source: &'a [u8],
+    position: usize,
+}
+
+impl Lexer<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    fn peek_next(&self) -> Option<u8> {
+        self.source.get(self.position + 1).copied()
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), ParserError> {
+        loop {
+            match (self.peek(), self.peek_next()) {
+                (Some(c), _) if c.is_ascii_whitespace() => self.position += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    while !matches!(self.peek(), Some(b'\n') | None) {
+                        self.position += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    let start = self.position;
+                    self.position += 2;
+                    loop {
+                        match (self.peek(), self.peek_next()) {
+                            (Some(b'*'), Some(b'/')) => {
+                                self.position += 2;
+                                break;
+                            }
+                            (Some(_), _) => self.position += 1,
+                            (None, _) => {
+                                return Err(ParserError::new(
+                                    "unterminated block comment",
+                                    Span::new(start, self.position),
+                                ))
+                            }
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<SpannedToken, ParserError> {
+        self.skip_whitespace_and_comments()?;
+        let start = self.position;
+        let Some(c) = self.peek() else {
+            return Ok(SpannedToken {
+                token: Token::Eof,
+                span: Span::new(start, start),
+            });
+        };
+
+        let token = if c.is_ascii_alphabetic() || c == b'_' {
+            self.lex_word()
+        } else if c.is_ascii_digit() {
+            self.lex_integer(start)?
+        } else if c == b'"' {
+            self.lex_string(start)?
+        } else {
+            self.lex_punctuation(start)?
+        };
+
+        Ok(SpannedToken {
+            token,
+            span: Span::new(start, self.position),
+        })
+    }
+
+    fn lex_word(&mut self) -> Token {
+        let start = self.position;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == b'_') {
+            self.position += 1;
+        }
+        let word = std::str::from_utf8(&self.source[start..self.position])
+            .expect("identifiers are ascii")
+            .to_owned();
+        match Keyword::lookup(&word) {
+            Some(keyword) => Token::Keyword(keyword),
+            None => Token::Ident(word),
+        }
+    }
+
+    fn lex_integer(&mut self, start: usize) -> Result<Token, ParserError> {
+        let radix = if self.peek() == Some(b'0') && self.peek_next() == Some(b'x') {
+            self.position += 2;
+            16
+        } else {
+            10
+        };
+        let is_digit = |c: u8| {
+            if radix == 16 {
+                c.is_ascii_hexdigit()
+            } else {
+                c.is_ascii_digit()
+            }
+        };
+        let digits_start = self.position;
+        while matches!(self.peek(), Some(c) if is_digit(c) || c == b'_') {
+            self.position += 1;
+        }
+        let digits: String = std::str::from_utf8(&self.source[digits_start..self.position])
+            .expect("integer literals are ascii")
+            .chars()
+            .filter(|c| *c != '_')
+            .collect();
+
+        let suffix_start = self.position;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == b'_') {
+            self.position += 1;
+        }
+        let span = Span::new(start, self.position);
+        let suffix = std::str::from_utf8(&self.source[suffix_start..self.position])
+            .expect("integer literals are ascii");
+        let value = u128::from_str_radix(&digits, radix)
+            .map_err(|_| ParserError::new("invalid integer literal", span))?;
+        if suffix.is_empty() {
+            return Ok(Token::Int(value));
+        }
+        let is_integer_suffix = matches!(suffix.as_bytes()[0], b'u' | b'i')
+            && suffix.len() > 1
+            && suffix[1..].bytes().all(|c| c.is_ascii_digit());
+        if radix != 10 || !is_integer_suffix {
+            return Err(ParserError::new("invalid integer literal", span));
+        }
+        Ok(Token::SuffixedInt(value, suffix.to_owned()))
+    }
+
+    fn lex_string(&mut self, start: usize) -> Result<Token, ParserError> {
+        self.position += 1;
+        let contents_start = self.position;
+        while !matches!(self.peek(), Some(b'"') | None) {
+            self.position += 1;
+        }
+        if self.peek().is_none() {
+            return Err(ParserError::new(
+                "unterminated string literal",
+                Span::new(start, self.position),
+            ));
+        }
+        let contents = String::from_utf8_lossy(&self.source[contents_start..self.position]);
+        self.position += 1;
+        Ok(Token::Str(contents.into_owned()))
+    }
+
+    fn lex_punctuation(&mut self, start: usize) -> Result<Token, ParserError> {
+        let c = self.peek().expect("checked by the caller");
+        let next = self.peek_next();
+        let (token, width) = match (c, next) {
+            (b'(', _) => (Token::LeftParen, 1),
+            (b')', _) => (Token::RightParen, 1),
+            (b'{', _) => (Token::LeftBrace, 1),
+            (b'}', _) => (Token::RightBrace, 1),
+            (b'[', _) => (Token::LeftBracket, 1),
+            (b']', _) => (Token::RightBracket, 1),
+            (b',', _) => (Token::Comma, 1),
+            (b';', _) => (Token::Semicolon, 1),
+            (b':', Some(b':')) => (Token::DoubleColon, 2),
+            (b':', _) => (Token::Colon, 1),
+            (b'.', Some(b'.')) => (Token::DoubleDot, 2),
+            (b'.', _) => (Token::Dot, 1),
+            (b'-', Some(b'>')) => (Token::Arrow, 2),
+            (b'-', _) => (Token::Minus, 1),
+            (b'#', _) => (Token::Pound, 1),
+            (b'=', Some(b'=')) => (Token::Equal, 2),
+            (b'=', _) => (Token::Assign, 1),
+            (b'!', Some(b'=')) => (Token::NotEqual, 2),
+            (b'!', _) => (Token::Bang, 1),
+            (b'<', Some(b'=')) => (Token::LessEqual, 2),
+            (b'<', Some(b'<')) => (Token::ShiftLeft, 2),
+            (b'<', _) => (Token::Less, 1),
+            (b'>', Some(b'=')) => (Token::GreaterEqual, 2),
+            (b'>', Some(b'>')) => (Token::ShiftRight, 2),
+            (b'>', _) => (Token::Greater, 1),
+            (b'+', _) => (Token::Plus, 1),
+            (b'*', _) => (Token::Star, 1),
+            (b'/', _) => (Token::Slash, 1),
+            (b'%', _) => (Token::Percent, 1),
+            (b'^', _) => (Token::Caret, 1),
+            // `&&` and `||` are accepted as spellings of the boolean operators.
+            (b'&', Some(b'&')) => (Token::Ampersand, 2),
+            (b'&', _) => (Token::Ampersand, 1),
+            (b'|', Some(b'|')) => (Token::Pipe, 2),
+            (b'|', _) => (Token::Pipe, 1),
+            _ => {
+                // Tokens end on character boundaries, so `start` begins one.
+                let character = std::str::from_utf8(&self.source[start..])
+                    .ok()
+                    .and_then(|rest| rest.chars().next())
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                return Err(ParserError::new(
+                    format!("unexpected character `{character}`"),
+                    Span::new(start, start + character.len_utf8()),
+                ));
+            }
+        };
+        self.position += width;
+        Ok(token)
+    }
+}
@@ -0,0 +1,12 @@
+//! A parser for the subset of the Noir language that can be lowered to Aleo
+//! instructions.
+//!
+//! The AST mirrors the shape of `noirc_frontend`'s parsed module so the
+//! compiler reads the same way it would against the upstream frontend.
+
+mod ast;
+mod lexer;
+mod parser;
+
+pub use ast::*;
+pub use parser::{parse_program, ParserError};
@@ -0,0 +1,917 @@
+use std::fmt;
+
+use crate::{
+    ast::*,
+    lexer::{tokenize, Keyword, SpannedToken, Token},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl ParserError {
+    pub(crate) fn new(message: impl Into<String>, span: Span) -> Self {
+        ParserError {
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ParserError {}
+
+/// Parses a Noir source file into a [`ParsedModule`].
+pub fn parse_program(source: &str) -> Result<ParsedModule, ParserError> {
+    let tokens = tokenize(source)?;
+    Parser {
+        tokens,
+        position: 0,
+    }
+    .parse_module()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Lowest,
+    Or,
+    Xor,
+    And,
+    LessGreater,
+    Shift,
+    Sum,
+    Product,
+}
+
+fn infix_operator(token: &Token) -> Option<(BinaryOpKind, Precedence)> {
+    let operator = match token {
+        Token::Pipe => (BinaryOpKind::Or, Precedence::Or),
+        Token::Caret => (BinaryOpKind::Xor, Precedence::Xor),
+        Token::Ampersand => (BinaryOpKind::And, Precedence::And),
+        Token::Equal => (BinaryOpKind::Equal, Precedence::LessGreater),
+        Token::NotEqual => (BinaryOpKind::NotEqual, Precedence::LessGreater),
+        Token::Less => (BinaryOpKind::Less, Precedence::LessGreater),
+        Token::LessEqual => (BinaryOpKind::LessEqual, Precedence::LessGreater),
+        Token::Greater => (BinaryOpKind::Greater, Precedence::LessGreater),
+        Token::GreaterEqual => (BinaryOpKind::GreaterEqual, Precedence::LessGreater),
+        Token::ShiftLeft => (BinaryOpKind::ShiftLeft, Precedence::Shift),
+        Token::ShiftRight => (BinaryOpKind::ShiftRight, Precedence::Shift),
+        Token::Plus => (BinaryOpKind::Add, Precedence::Sum),
+        Token::Minus => (BinaryOpKind::Subtract, Precedence::Sum),
+        Token::Star => (BinaryOpKind::Multiply, Precedence::Product),
+        Token::Slash => (BinaryOpKind::Divide, Precedence::Product),
+        Token::Percent => (BinaryOpKind::Modulo, Precedence::Product),
+        _ => return None,
+    };
+    Some(operator)
+}
+
+struct Parser {
+    tokens: Vec<SpannedToken>,
+    position: usize,
+}
+
+type ParseResult<T> = Result<T, ParserError>;
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].token
+    }
+
+    fn peek_nth(&self, n: usize) -> &Token {
+        let index = (self.position + n).min(self.tokens.len() - 1);
+        &self.tokens[index].token
+    }
+
+    fn span(&self) -> Span {
+        self.tokens[self.position].span
+    }
+
+    fn previous_span(&self) -> Span {
+        self.tokens[self.position.saturating_sub(1)].span
+    }
+
+    fn advance(&mut self) -> SpannedToken {
+        let token = self.tokens[self.position].clone();
+        if token.token != Token::Eof {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == token {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: Keyword) -> bool {
+        self.eat(&Token::Keyword(keyword))
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> ParseResult<Span> {
+        if self.peek() == &token {
+            Ok(self.advance().span)
+        } else {
+            Err(self.unexpected(what))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParserError {
+        let found = match self.peek() {
+            Token::Eof => "end of file".to_owned(),
+            token => format!("{token:?}"),
+        };
+        ParserError::new(format!("expected {expected}, found {found}"), self.span())
+    }
+
+    fn parse_ident(&mut self) -> ParseResult<Ident> {
+        match self.peek().clone() {
+            Token::Ident(name) => {
+                let span = self.advance().span;
+                Ok(Ident { name, span })
+            }
+            _ => Err(self.unexpected("an identifier")),
+        }
+    }
+
+    fn parse_path(&mut self) -> ParseResult<Path> {
+        let mut segments = vec![self.parse_ident()?];
+        while self.peek() == &Token::DoubleColon && matches!(self.peek_nth(1), Token::Ident(_)) {
+            self.advance();
+            segments.push(self.parse_ident()?);
+        }
+        Ok(Path { segments })
+    }
+
+    /// Parses a comma separated list up to (and including) the `close` token.
+    fn parse_list<T>(
+        &mut self,
+        close: Token,
+        mut parse_item: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        let mut items = Vec::new();
+        while self.peek() != &close {
+            items.push(parse_item(self)?);
+            if !self.eat(&Token::Comma) {
+                break;
+            }
+        }
+        self.expect(close, "a closing delimiter")?;
+        Ok(items)
+    }
+
+    fn parse_module(&mut self) -> ParseResult<ParsedModule> {
+        let mut module = ParsedModule::default();
+        while self.peek() != &Token::Eof {
+            let attributes = self.parse_attributes()?;
+            match self.peek() {
+                Token::Keyword(Keyword::Fn) => {
+                    module.functions.push(self.parse_function(attributes)?)
+                }
+                Token::Keyword(Keyword::Struct) => {
+                    module.types.push(self.parse_struct(attributes)?)
+                }
+                Token::Keyword(Keyword::Global) => {
+                    module.globals.push(self.parse_global(attributes)?)
+                }
+                Token::Keyword(Keyword::Use) if attributes.is_empty() => {
+                    module.imports.push(self.parse_import()?)
+                }
+                _ => return Err(self.unexpected("a function, struct, global or use declaration")),
+            }
+        }
+        Ok(module)
+    }
+
+    fn parse_attributes(&mut self) -> ParseResult<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        while self.peek() == &Token::Pound {
+            let start = self.advance().span;
+            self.expect(Token::LeftBracket, "`[`")?;
+            let name = self.parse_ident()?.name;
+            let mut arguments = Vec::new();
+            if self.eat(&Token::LeftParen) {
+                arguments =
+                    self.parse_list(Token::RightParen, |parser| match parser.peek().clone() {
+                        Token::Ident(name) | Token::Str(name) => {
+                            parser.advance();
+                            Ok(name)
+                        }
+                        Token::Int(value) => {
+                            parser.advance();
+                            Ok(value.to_string())
+                        }
+                        _ => Err(parser.unexpected("an attribute argument")),
+                    })?;
+            }
+            let end = self.expect(Token::RightBracket, "`]`")?;
+            attributes.push(Attribute {
+                name,
+                arguments,
+                span: start.merge(end),
+            });
+        }
+        Ok(attributes)
+    }
+
+    fn parse_import(&mut self) -> ParseResult<ImportStatement> {
+        self.advance();
+        let path = self.parse_path()?;
+        let alias = if self.eat_keyword(Keyword::As) {
+            Some(self.parse_ident()?)
+        } else {
+            None
+        };
+        self.expect(Token::Semicolon, "`;`")?;
+        Ok(ImportStatement { path, alias })
+    }
+
+    fn parse_visibility(&mut self) -> AbiFEType {
+        if self.eat_keyword(Keyword::Pub) {
+            AbiFEType::Public
+        } else {
+            AbiFEType::Private
+        }
+    }
+
+    fn parse_function(&mut self, attributes: Vec<Attribute>) -> ParseResult<FunctionDefinition> {
+        let start = self.advance().span;
+        let name = self.parse_ident()?;
+        self.expect(Token::LeftParen, "`(`")?;
+        let parameters = self.parse_list(Token::RightParen, |parser| {
+            let pattern = parser.parse_pattern()?;
+            parser.expect(Token::Colon, "`:`")?;
+            let visibility = parser.parse_visibility();
+            let typ = parser.parse_type()?;
+            Ok((pattern, typ, visibility))
+        })?;
+        let (return_type, return_visibility, return_distinctness) = if self.eat(&Token::Arrow) {
+            let distinctness = if self.eat_keyword(Keyword::Distinct) {
+                Distinctness::Distinct
+            } else {
+                Distinctness::DuplicationAllowed
+            };
+            let visibility = self.parse_visibility();
+            (self.parse_type()?, visibility, distinctness)
+        } else {
+            (
+                UnresolvedType::Unit,
+                AbiFEType::Private,
+                Distinctness::DuplicationAllowed,
+            )
+        };
+        let body = self.parse_block()?;
+        Ok(FunctionDefinition {
+            name,
+            attributes,
+            parameters,
+            body,
+            span: start.merge(self.previous_span()),
+            return_type,
+            return_visibility,
+            return_distinctness,
+        })
+    }
+
+    fn parse_struct(&mut self, attributes: Vec<Attribute>) -> ParseResult<NoirStruct> {
+        let start = self.advance().span;
+        let name = self.parse_ident()?;
+        self.expect(Token::LeftBrace, "`{`")?;
+        let fields = self.parse_list(Token::RightBrace, |parser| {
+            let field = parser.parse_ident()?;
+            parser.expect(Token::Colon, "`:`")?;
+            Ok((field, parser.parse_type()?))
+        })?;
+        Ok(NoirStruct {
+            name,
+            attributes,
+            fields,
+            span: start.merge(self.previous_span()),
+        })
+    }
+
+    fn parse_global(&mut self, attributes: Vec<Attribute>) -> ParseResult<GlobalDefinition> {
+        let start = self.advance().span;
+        let name = self.parse_ident()?;
+        let r#type = if self.eat(&Token::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        self.expect(Token::Assign, "`=`")?;
+        let expression = self.parse_expression()?;
+        let end = self.expect(Token::Semicolon, "`;`")?;
+        Ok(GlobalDefinition {
+            name,
+            attributes,
+            r#type,
+            expression,
+            span: start.merge(end),
+        })
+    }
+
+    fn parse_pattern(&mut self) -> ParseResult<Pattern> {
+        let start = self.span();
+        if self.eat_keyword(Keyword::Mut) {
+            let pattern = self.parse_pattern()?;
+            return Ok(Pattern::Mutable(
+                Box::new(pattern),
+                start.merge(self.previous_span()),
+            ));
+        }
+        if self.eat(&Token::LeftParen) {
+            let patterns = self.parse_list(Token::RightParen, Self::parse_pattern)?;
+            return Ok(Pattern::Tuple(patterns, start.merge(self.previous_span())));
+        }
+        let path = self.parse_path()?;
+        if self.eat(&Token::LeftBrace) {
+            let fields = self.parse_list(Token::RightBrace, |parser| {
+                let field = parser.parse_ident()?;
+                let pattern = if parser.eat(&Token::Colon) {
+                    parser.parse_pattern()?
+                } else {
+                    Pattern::Identifier(field.clone())
+                };
+                Ok((field, pattern))
+            })?;
+            return Ok(Pattern::Struct(
+                path,
+                fields,
+                start.merge(self.previous_span()),
+            ));
+        }
+        match path.as_ident() {
+            Some(ident) => Ok(Pattern::Identifier(ident.clone())),
+            None => Err(ParserError::new("expected a pattern", path.span())),
+        }
+    }
+
+    fn parse_type(&mut self) -> ParseResult<UnresolvedType> {
+        if self.eat(&Token::LeftBracket) {
+            let element = self.parse_type()?;
+            let length = if self.eat(&Token::Semicolon) {
+                Some(Box::new(self.parse_expression()?))
+            } else {
+                None
+            };
+            self.expect(Token::RightBracket, "`]`")?;
+            return Ok(UnresolvedType::Array(length, Box::new(element)));
+        }
+        if self.eat(&Token::LeftParen) {
+            let mut elements = Vec::new();
+            let mut trailing_comma = false;
+            while self.peek() != &Token::RightParen {
+                elements.push(self.parse_type()?);
+                trailing_comma = self.eat(&Token::Comma);
+                if !trailing_comma {
+                    break;
+                }
+            }
+            self.expect(Token::RightParen, "`)`")?;
+            return Ok(match elements.len() {
+                0 => UnresolvedType::Unit,
+                1 if !trailing_comma => elements.remove(0),
+                _ => UnresolvedType::Tuple(elements),
+            });
+        }
+
+        let path = self.parse_path()?;
+        if let Some(ident) = path.as_ident() {
+            if let Some(typ) = primitive_type(&ident.name) {
+                return Ok(typ);
+            }
+        }
+        Ok(UnresolvedType::Named(path))
+    }
+
+    fn parse_block(&mut self) -> ParseResult<BlockExpression> {
+        self.expect(Token::LeftBrace, "`{`")?;
+        let mut statements = Vec::new();
+        while !self.eat(&Token::RightBrace) {
+            if self.peek() == &Token::Eof {
+                return Err(self.unexpected("`}`"));
+            }
+            if self.eat(&Token::Semicolon) {
+                continue;
+            }
+            statements.push(self.parse_statement()?);
+        }
+        Ok(BlockExpression(statements))
+    }
+
+    fn parse_statement(&mut self) -> ParseResult<Statement> {
+        if self.eat_keyword(Keyword::Let) {
+            let pattern = self.parse_pattern()?;
+            let r#type = if self.eat(&Token::Colon) {
+                Some(self.parse_type()?)
+            } else {
+                None
+            };
+            self.expect(Token::Assign, "`=`")?;
+            let expression = self.parse_expression()?;
+            self.expect(Token::Semicolon, "`;`")?;
+            return Ok(Statement::Let(LetStatement {
+                pattern,
+                r#type,
+                expression,
+            }));
+        }
+        if self.eat_keyword(Keyword::Constrain) {
+            let expression = self.parse_expression()?;
+            self.expect(Token::Semicolon, "`;`")?;
+            return Ok(Statement::Constrain(ConstrainStatement(expression)));
+        }
+        if matches!(self.peek(), Token::Ident(name) if name == "assert")
+            && self.peek_nth(1) == &Token::LeftParen
+        {
+            self.advance();
+            self.advance();
+            let expression = self.parse_expression()?;
+            self.expect(Token::RightParen, "`)`")?;
+            self.expect(Token::Semicolon, "`;`")?;
+            return Ok(Statement::Constrain(ConstrainStatement(expression)));
+        }
+
+        let expression = self.parse_expression()?;
+        if self.eat(&Token::Assign) {
+            let lvalue = into_lvalue(expression)?;
+            let expression = self.parse_expression()?;
+            self.expect(Token::Semicolon, "`;`")?;
+            return Ok(Statement::Assign(AssignStatement { lvalue, expression }));
+        }
+        if self.eat(&Token::Semicolon) {
+            return Ok(Statement::Semi(expression));
+        }
+        if self.peek() == &Token::RightBrace || ends_with_block(&expression) {
+            return Ok(Statement::Expression(expression));
+        }
+        Err(self.unexpected("`;`"))
+    }
+
+    fn parse_expression(&mut self) -> ParseResult<Expression> {
+        self.parse_expression_with(Precedence::Lowest, true)
+    }
+
+    /// Parses an expression where a struct constructor would be ambiguous with
+    /// a following block, e.g. an `if` condition.
+    fn parse_expression_no_struct(&mut self) -> ParseResult<Expression> {
+        self.parse_expression_with(Precedence::Lowest, false)
+    }
+
+    fn parse_expression_with(
+        &mut self,
+        precedence: Precedence,
+        allow_struct: bool,
+    ) -> ParseResult<Expression> {
+        let mut lhs = self.parse_cast(allow_struct)?;
+        while let Some((operator, operator_precedence)) = infix_operator(self.peek()) {
+            if operator_precedence <= precedence {
+                break;
+            }
+            self.advance();
+            let rhs = self.parse_expression_with(operator_precedence, allow_struct)?;
+            let span = lhs.span.merge(rhs.span);
+            lhs = Expression::new(
+                ExpressionKind::Infix(Box::new(InfixExpression { lhs, operator, rhs })),
+                span,
+            );
+        }
+        Ok(lhs)
+    }
+
+    fn parse_cast(&mut self, allow_struct: bool) -> ParseResult<Expression> {
+        let mut lhs = self.parse_prefix(allow_struct)?;
+        while self.eat_keyword(Keyword::As) {
+            let r#type = self.parse_type()?;
+            let span = lhs.span.merge(self.previous_span());
+            lhs = Expression::new(
+                ExpressionKind::Cast(Box::new(CastExpression { lhs, r#type })),
+                span,
+            );
+        }
+        Ok(lhs)
+    }
+
+    fn parse_prefix(&mut self, allow_struct: bool) -> ParseResult<Expression> {
+        let start = self.span();
+        let operator = match self.peek() {
+            Token::Minus => UnaryOp::Minus,
+            Token::Bang => UnaryOp::Not,
+            _ => return self.parse_postfix(allow_struct),
+        };
+        self.advance();
+        let rhs = self.parse_prefix(allow_struct)?;
+        let span = start.merge(rhs.span);
+        Ok(Expression::new(
+            ExpressionKind::Prefix(Box::new(PrefixExpression { operator, rhs })),
+            span,
+        ))
+    }
+
+    fn parse_postfix(&mut self, allow_struct: bool) -> ParseResult<Expression> {
+        let mut expression = self.parse_primary(allow_struct)?;
+        loop {
+            match self.peek() {
+                Token::LeftParen => {
+                    self.advance();
+                    let arguments = self.parse_list(Token::RightParen, Self::parse_expression)?;
+                    let span = expression.span.merge(self.previous_span());
+                    let call = CallExpression {
+                        func: Box::new(expression),
+                        arguments,
+                    };
+                    expression = Expression::new(ExpressionKind::Call(Box::new(call)), span);
+                }
+                Token::LeftBracket => {
+                    self.advance();
+                    let index = self.parse_expression()?;
+                    let end = self.expect(Token::RightBracket, "`]`")?;
+                    let span = expression.span.merge(end);
+                    let index = IndexExpression {
+                        collection: expression,
+                        index,
+                    };
+                    expression = Expression::new(ExpressionKind::Index(Box::new(index)), span);
+                }
+                Token::Dot => {
+                    self.advance();
+                    let rhs = match self.peek().clone() {
+                        Token::Int(index) => {
+                            let span = self.advance().span;
+                            Ident {
+                                name: index.to_string(),
+                                span,
+                            }
+                        }
+                        _ => self.parse_ident()?,
+                    };
+                    if self.peek() == &Token::LeftParen {
+                        self.advance();
+                        let arguments =
+                            self.parse_list(Token::RightParen, Self::parse_expression)?;
+                        let span = expression.span.merge(self.previous_span());
+                        let call = MethodCallExpression {
+                            object: expression,
+                            method_name: rhs,
+                            arguments,
+                        };
+                        expression =
+                            Expression::new(ExpressionKind::MethodCall(Box::new(call)), span);
+                    } else {
+                        let span = expression.span.merge(rhs.span);
+                        let access = MemberAccessExpression {
+                            lhs: expression,
+                            rhs,
+                        };
+                        expression =
+                            Expression::new(ExpressionKind::MemberAccess(Box::new(access)), span);
+                    }
+                }
+                _ => return Ok(expression),
+            }
+        }
+    }
+
+    fn parse_primary(&mut self, allow_struct: bool) -> ParseResult<Expression> {
+        let start = self.span();
+        let kind = match self.peek().clone() {
+            Token::Int(value) => {
+                self.advance();
+                ExpressionKind::Literal(Literal::Integer(value, None))
+            }
+            Token::SuffixedInt(value, suffix) => {
+                self.advance();
+                let suffix = primitive_type(&suffix);
+                ExpressionKind::Literal(Literal::Integer(value, suffix))
+            }
+            Token::Str(value) => {
+                self.advance();
+                ExpressionKind::Literal(Literal::Str(value))
+            }
+            Token::Keyword(Keyword::True) => {
+                self.advance();
+                ExpressionKind::Literal(Literal::Bool(true))
+            }
+            Token::Keyword(Keyword::False) => {
+                self.advance();
+                ExpressionKind::Literal(Literal::Bool(false))
+            }
+            Token::Ident(_) => {
+                let path = self.parse_path()?;
+                if allow_struct && self.peek() == &Token::LeftBrace && self.looks_like_constructor()
+                {
+                    self.advance();
+                    let fields = self.parse_list(Token::RightBrace, |parser| {
+                        let field = parser.parse_ident()?;
+                        let value = if parser.eat(&Token::Colon) {
+                            parser.parse_expression()?
+                        } else {
+                            let path = Path {
+                                segments: vec![field.clone()],
+                            };
+                            Expression::new(ExpressionKind::Path(path), field.span)
+                        };
+                        Ok((field, value))
+                    })?;
+                    ExpressionKind::Constructor(Box::new(ConstructorExpression {
+                        type_name: path,
+                        fields,
+                    }))
+                } else {
+                    ExpressionKind::Path(path)
+                }
+            }
+            Token::LeftParen => {
+                self.advance();
+                let mut elements = Vec::new();
+                let mut trailing_comma = false;
+                while self.peek() != &Token::RightParen {
+                    elements.push(self.parse_expression()?);
+                    trailing_comma = self.eat(&Token::Comma);
+                    if !trailing_comma {
+                        break;
+                    }
+                }
+                self.expect(Token::RightParen, "`)`")?;
+                if elements.len() == 1 && !trailing_comma {
+                    let mut inner = elements.remove(0);
+                    inner.span = start.merge(self.previous_span());
+                    return Ok(inner);
+                }
+                ExpressionKind::Tuple(elements)
+            }
+            Token::LeftBracket => {
+                self.advance();
+                if self.eat(&Token::RightBracket) {
+                    ExpressionKind::Literal(Literal::Array(ArrayLiteral::Standard(Vec::new())))
+                } else {
+                    let first = self.parse_expression()?;
+                    if self.eat(&Token::Semicolon) {
+                        let length = self.parse_expression()?;
+                        self.expect(Token::RightBracket, "`]`")?;
+                        ExpressionKind::Literal(Literal::Array(ArrayLiteral::Repeated {
+                            repeated_element: Box::new(first),
+                            length: Box::new(length),
+                        }))
+                    } else {
+                        let mut elements = vec![first];
+                        if self.eat(&Token::Comma) {
+                            elements.extend(
+                                self.parse_list(Token::RightBracket, Self::parse_expression)?,
+                            );
+                        } else {
+                            self.expect(Token::RightBracket, "`]`")?;
+                        }
+                        ExpressionKind::Literal(Literal::Array(ArrayLiteral::Standard(elements)))
+                    }
+                }
+            }
+            Token::LeftBrace => ExpressionKind::Block(self.parse_block()?),
+            Token::Keyword(Keyword::If) => {
+                self.advance();
+                let condition = self.parse_expression_no_struct()?;
+                let consequence = self.parse_block_expression()?;
+                let alternative = if self.eat_keyword(Keyword::Else) {
+                    if self.peek() == &Token::Keyword(Keyword::If) {
+                        Some(self.parse_primary(true)?)
+                    } else {
+                        Some(self.parse_block_expression()?)
+                    }
+                } else {
+                    None
+                };
+                ExpressionKind::If(Box::new(IfExpression {
+                    condition,
+                    consequence,
+                    alternative,
+                }))
+            }
+            Token::Keyword(Keyword::For) => {
+                self.advance();
+                let identifier = self.parse_ident()?;
+                if !self.eat_keyword(Keyword::In) {
+                    return Err(self.unexpected("`in`"));
+                }
+                let start_range = self.parse_expression_no_struct()?;
+                self.expect(Token::DoubleDot, "`..`")?;
+                let end_range = self.parse_expression_no_struct()?;
+                let block = self.parse_block_expression()?;
+                ExpressionKind::For(Box::new(ForExpression {
+                    identifier,
+                    start_range,
+                    end_range,
+                    block,
+                }))
+            }
+            _ => return Err(self.unexpected("an expression")),
+        };
+        Ok(Expression::new(kind, start.merge(self.previous_span())))
+    }
+
+    fn parse_block_expression(&mut self) -> ParseResult<Expression> {
+        let start = self.span();
+        let block = self.parse_block()?;
+        Ok(Expression::new(
+            ExpressionKind::Block(block),
+            start.merge(self.previous_span()),
+        ))
+    }
+
+    /// Distinguishes `Foo { x: 1 }` from a path followed by an unrelated block.
+    fn looks_like_constructor(&self) -> bool {
+        matches!(
+            (self.peek_nth(1), self.peek_nth(2)),
+            (Token::RightBrace, _)
+                | (
+                    Token::Ident(_),
+                    Token::Colon | Token::Comma | Token::RightBrace
+                )
+        )
+    }
+}
+
+fn primitive_type(name: &str) -> Option<UnresolvedType> {
+    match name {
+        "Field" => Some(UnresolvedType::FieldElement),
+        "bool" => Some(UnresolvedType::Bool),
+        _ => {
+            let signedness = match name.as_bytes().first()? {
+                b'u' => Signedness::Unsigned,
+                b'i' => Signedness::Signed,
+                _ => return None,
+            };
+            let bits = &name[1..];
+            if bits.is_empty() || !bits.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            Some(UnresolvedType::Integer(signedness, bits.parse().ok()?))
+        }
+    }
+}
+
+fn ends_with_block(expression: &Expression) -> bool {
+    matches!(
+        expression.kind,
+        ExpressionKind::Block(_) | ExpressionKind::If(_) | ExpressionKind::For(_)
+    )
+}
+
+fn into_lvalue(expression: Expression) -> ParseResult<LValue> {
+    match expression.kind {
+        ExpressionKind::Path(path) => match path.as_ident() {
+            Some(ident) => Ok(LValue::Ident(ident.clone())),
+            None => Err(ParserError::new("cannot assign to a path", expression.span)),
+        },
+        ExpressionKind::MemberAccess(access) => Ok(LValue::MemberAccess {
+            object: Box::new(into_lvalue(access.lhs)?),
+            field_name: access.rhs,
+        }),
+        ExpressionKind::Index(index) => Ok(LValue::Index {
+            array: Box::new(into_lvalue(index.collection)?),
+            index: index.index,
+        }),
+        _ => Err(ParserError::new(
+            "invalid assignment target",
+            expression.span,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_body(source: &str) -> Vec<Statement> {
+        let module = parse_program(source).unwrap();
+        module.functions[0].body.0.clone()
+    }
+
+    #[test]
+    fn test_parse_function_signature() {
+        let module = parse_program("fn main(x: u32, y: pub Field) -> pub bool { x == y }").unwrap();
+        let function = &module.functions[0];
+        assert_eq!(function.name.name, "main");
+        assert_eq!(function.parameters.len(), 2);
+        assert_eq!(
+            function.parameters[0].1,
+            UnresolvedType::Integer(Signedness::Unsigned, 32)
+        );
+        assert_eq!(function.parameters[1].1, UnresolvedType::FieldElement);
+        assert_eq!(function.parameters[1].2, AbiFEType::Public);
+        assert_eq!(function.return_type, UnresolvedType::Bool);
+        assert_eq!(function.return_visibility, AbiFEType::Public);
+        assert_eq!(
+            function.return_distinctness,
+            Distinctness::DuplicationAllowed
+        );
+    }
+
+    #[test]
+    fn test_parse_distinct_return() {
+        let module = parse_program("fn main(x: u32) -> distinct pub [u32; 2] { [x, x] }").unwrap();
+        let function = &module.functions[0];
+        assert_eq!(function.return_distinctness, Distinctness::Distinct);
+        assert_eq!(function.return_visibility, AbiFEType::Public);
+        assert_eq!(function.return_type.to_string(), "[u32; 2]");
+    }
+
+    #[test]
+    fn test_infix_precedence() {
+        let statements = parse_body("fn main(a: u32, b: u32, c: u32) -> u32 { a + b * c }");
+        let Statement::Expression(Expression {
+            kind: ExpressionKind::Infix(infix),
+            ..
+        }) = &statements[0]
+        else {
+            panic!("expected an infix tail expression");
+        };
+        assert_eq!(infix.operator, BinaryOpKind::Add);
+        assert!(
+            matches!(&infix.rhs.kind, ExpressionKind::Infix(rhs) if rhs.operator == BinaryOpKind::Multiply)
+        );
+    }
+
+    #[test]
+    fn test_parse_statements() {
+        let statements = parse_body(
+            "fn main(x: Field) { let mut y = x; constrain y == x; y = x + 1; assert(x != 0); }",
+        );
+        assert!(matches!(
+            &statements[0],
+            Statement::Let(LetStatement {
+                pattern: Pattern::Mutable(..),
+                ..
+            })
+        ));
+        assert!(matches!(&statements[1], Statement::Constrain(_)));
+        assert!(matches!(&statements[2], Statement::Assign(_)));
+        assert!(matches!(&statements[3], Statement::Constrain(_)));
+    }
+
+    #[test]
+    fn test_constructor_is_not_parsed_in_if_condition() {
+        let statements = parse_body("fn main(x: bool) -> Field { if x { 1 } else { 2 } }");
+        assert!(matches!(
+            &statements[0],
+            Statement::Expression(Expression {
+                kind: ExpressionKind::If(_),
+                ..
+            })
+        ));
+
+        let module =
+            parse_program("struct P { x: Field } fn main(a: Field) -> P { P { x: a } }").unwrap();
+        let statement = &module.functions[0].body.0[0];
+        assert!(matches!(
+            statement,
+            Statement::Expression(Expression {
+                kind: ExpressionKind::Constructor(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_suffixed_integer_literal() {
+        let statements = parse_body("fn main(x: u32) -> u32 { x.pow(2u32) }");
+        let Statement::Expression(Expression {
+            kind: ExpressionKind::MethodCall(call),
+            ..
+        }) = &statements[0]
+        else {
+            panic!("expected a method call");
+        };
+        assert_eq!(
+            call.arguments[0].kind,
+            ExpressionKind::Literal(Literal::Integer(
+                2,
+                Some(UnresolvedType::Integer(Signedness::Unsigned, 32))
+            ))
+        );
+        assert!(parse_program("fn main() { 2u }").is_err());
+    }
+
+    #[test]
+    fn test_parse_error_has_span() {
+        let error = parse_program("fn main(x: u32) { let = x; }").unwrap_err();
+        assert_eq!(error.span, Span::new(22, 23));
+    }
+
+    #[test]
+    fn test_unexpected_character_error_covers_the_whole_character() {
+        let error = parse_program("fn main(x: u32) { x é }").unwrap_err();
+        assert_eq!(error.message, "unexpected character `é`");
+        assert_eq!(error.span, Span::new(20, 22));
+    }
+}
@@ -0,0 +1,65 @@
+//! Compiles a Noir program through the library rather than the command line,
+//! as a crate embedding the compiler would:
+//!
+//! ```text
+//! cargo run --example compile_api -- [output dir]
+//! ```
+//!
+//! The program is written to the output directory, a temporary one by
+//! default.
+
+use std::{env, path::PathBuf, process::ExitCode};
+
+use noir_to_aleo_instructions::{compile_source, write, CompileOptions, OutputConfig};
+
+const SOURCE: &str = "
+fn main(x : u32, y : pub u32) -> pub u32 {
+    let cube = x * x * x;
+    cube + y * y * y
+}
+";
+
+fn main() -> ExitCode {
+    let output_dir = env::args_os()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| env::temp_dir().join("compile_api"));
+    let options = CompileOptions {
+        program_name: Some("cubes".to_owned()),
+        optimize: true,
+        ..CompileOptions::default()
+    };
+    let program = match compile_source(SOURCE, "main", &options) {
+        Ok(program) => program,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!("compiled {}", program.file_name());
+    for warning in program.warnings() {
+        println!("warning: {warning}");
+    }
+    let instructions = program
+        .aleo_program()
+        .lines()
+        .filter_map(|line| line.strip_prefix("    "))
+        .filter(|line| !line.starts_with("input ") && !line.starts_with("output "))
+        .count();
+    println!("instructions: {instructions}");
+    print!("{}", program.profile().to_table());
+    print!("\n{}\n", program.aleo_program());
+
+    let output_config = OutputConfig { output_dir };
+    match write(&program, &output_config) {
+        Ok(path) => {
+            println!("wrote {}", path.display());
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
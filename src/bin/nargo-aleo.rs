@@ -0,0 +1,6 @@
+//! `nargo aleo`: nargo runs `nargo-aleo` from the `PATH` for its unknown
+//! subcommands.
+
+fn main() {
+    noir_to_aleo_instructions::run_as_nargo_subcommand(std::env::args_os());
+}
@@ -0,0 +1,239 @@
+//! Writes the IR as Aleo instructions. This is the one place that decides how
+//! a program is laid out as text.
+
+use std::fmt;
+
+use crate::{
+    ir::{
+        AleoFunction, AleoProgram, Composite, Input, Instruction, Item, Mapping, Opcode, Operand,
+        Output, Register,
+    },
+    CallableKind, Visibility,
+};
+
+const INDENT: &str = "    ";
+
+/// Writes the header, then every item after one blank line, and ends with a
+/// single newline.
+impl fmt::Display for AleoProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "program {}.aleo;", self.name)?;
+        for item in &self.items {
+            writeln!(f)?;
+            write!(f, "{item}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every item ends with a newline and has no blank lines of its own.
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::Struct(composite) => write_composite(f, "struct", composite, None),
+            Item::Record(composite) => {
+                write_composite(f, "record", composite, Some(Visibility::Private))
+            }
+            Item::Mapping(mapping) => write!(f, "{mapping}"),
+            Item::Function(function) => write!(f, "{function}"),
+        }
+    }
+}
+
+fn write_composite(
+    f: &mut fmt::Formatter<'_>,
+    keyword: &str,
+    composite: &Composite,
+    visibility: Option<Visibility>,
+) -> fmt::Result {
+    writeln!(f, "{keyword} {}:", composite.name)?;
+    for (member, typ) in &composite.members {
+        writeln!(
+            f,
+            "{INDENT}{member} as {typ}{};",
+            visibility_suffix(visibility)
+        )?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Mapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "mapping {}:", self.name)?;
+        writeln!(f, "{INDENT}key as {}.public;", self.key_type)?;
+        writeln!(f, "{INDENT}value as {}.public;", self.value_type)
+    }
+}
+
+/// The `finalize` command comes after the outputs, directly followed by the
+/// block it calls.
+impl fmt::Display for AleoFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}:", self.kind.keyword(), self.name)?;
+        for input in &self.inputs {
+            writeln!(f, "{INDENT}{input};")?;
+        }
+        for instruction in &self.instructions {
+            writeln!(f, "{INDENT}{instruction};")?;
+        }
+        for output in &self.outputs {
+            writeln!(f, "{INDENT}{output};")?;
+        }
+        if let Some((operands, block)) = &self.finalize {
+            writeln!(f, "{INDENT}finalize {};", join(operands))?;
+            write!(f, "{block}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "input {} as {}{}",
+            self.register,
+            self.typ,
+            visibility_suffix(self.visibility)
+        )
+    }
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "output {} as {}{}",
+            self.operand,
+            self.typ,
+            visibility_suffix(self.visibility)
+        )
+    }
+}
+
+fn visibility_suffix(visibility: Option<Visibility>) -> String {
+    visibility.map_or_else(String::new, |visibility| format!(".{visibility}"))
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+        })
+    }
+}
+
+/// An instruction, without its indentation and trailing `;`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Operation {
+                opcode,
+                operands,
+                destination,
+            } => write!(f, "{opcode} {} into {destination}", join(operands)),
+            Instruction::Cast {
+                operands,
+                destination,
+                typ,
+            } => {
+                write!(f, "cast {} into {destination}", join(operands))?;
+                match typ {
+                    Some(typ) => write!(f, " as {typ}"),
+                    None => Ok(()),
+                }
+            }
+            Instruction::Assert { opcode, lhs, rhs } => write!(f, "{opcode} {lhs} {rhs}"),
+            Instruction::AssertTrue { operand } => write!(f, "assert {operand}"),
+            Instruction::Call {
+                callee,
+                operands,
+                destinations,
+            } => {
+                write!(f, "call {callee}")?;
+                for operand in operands {
+                    write!(f, " {operand}")?;
+                }
+                if !destinations.is_empty() {
+                    write!(f, " into {}", join(destinations))?;
+                }
+                Ok(())
+            }
+            Instruction::GetOrUse {
+                mapping,
+                key,
+                default,
+                destination,
+            } => write!(
+                f,
+                "get.or_use {mapping}[{key}] {default} into {destination}"
+            ),
+            Instruction::Contains {
+                mapping,
+                key,
+                destination,
+            } => write!(f, "contains {mapping}[{key}] into {destination}"),
+            Instruction::Set {
+                value,
+                mapping,
+                key,
+            } => write!(f, "set {value} into {mapping}[{key}]"),
+            Instruction::Remove { mapping, key } => write!(f, "remove {mapping}[{key}]"),
+            Instruction::Hash {
+                opcode,
+                operands,
+                destination,
+                typ,
+            } => write!(f, "{opcode} {} into {destination} as {typ}", join(operands)),
+        }
+    }
+}
+
+/// The number of columns `instruction` takes on its line.
+pub fn line_width(instruction: &Instruction) -> usize {
+    INDENT.len() + instruction.to_string().chars().count() + 1
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Register(register) => write!(f, "{register}"),
+            Operand::Integer { value, typ } => write!(f, "{value}{typ}"),
+            Operand::Boolean(value) => write!(f, "{value}"),
+            Operand::Member { base, member } => write!(f, "{base}.{member}"),
+            Operand::Index { base, index } => write!(f, "{base}[{index}u32]"),
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "r{}", self.0)
+    }
+}
+
+fn join(values: &[impl fmt::Display]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl CallableKind {
+    fn keyword(self) -> &'static str {
+        match self {
+            CallableKind::Transition => "transition",
+            CallableKind::Function => "function",
+            CallableKind::Closure => "closure",
+            CallableKind::Finalize => "finalize",
+        }
+    }
+}
@@ -0,0 +1,511 @@
+//! Errors reported while compiling a Noir package to Aleo instructions.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use noir_parser::{ParserError, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompileError {
+    MissingManifest(PathBuf),
+    InvalidManifest {
+        path: PathBuf,
+        message: String,
+    },
+    UnsupportedDependencies,
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    Parse(ParserError),
+    UnsupportedExpression(&'static str),
+    UnsupportedType(String),
+    UnsupportedStatement(&'static str),
+    UnsupportedPattern(&'static str),
+    UndeclaredVariable(String),
+    /// A name read before the `let` of the same body binding it.
+    UsedBeforeBinding(String),
+    /// A global whose value reads the global itself, directly or through
+    /// other globals.
+    CyclicGlobal(String),
+    /// An `if` statement computing an operation that can fail, which it would
+    /// also compute when its condition does not hold.
+    FallibleBranch,
+    IntegerLiteralOutOfRange {
+        value: u128,
+        typ: String,
+    },
+    TypeMismatch {
+        expected: String,
+        found: String,
+    },
+    MissingReturnValue {
+        function: String,
+        return_type: String,
+    },
+    UnsupportedCast {
+        from: String,
+        to: String,
+    },
+    TupleArityMismatch {
+        expected: usize,
+        found: usize,
+    },
+    UndeclaredFunction(String),
+    UnsupportedExternalCall(String),
+    /// A call to a standard library function that is not compiled to an
+    /// Aleo instruction, with why for those known to have none.
+    NoAleoEquivalent {
+        path: String,
+        reason: Option<&'static str>,
+    },
+    /// An argument of a standard library function compiled to an Aleo
+    /// instruction that the instruction does not accept.
+    InvalidIntrinsicArgument {
+        intrinsic: String,
+        found: String,
+        accepted: Vec<String>,
+    },
+    /// An entry of `aleo_intrinsics.toml` for the function at `path`.
+    InvalidIntrinsic {
+        path: String,
+        reason: String,
+    },
+    NonLiteralArrayLength(Span),
+    UndeclaredStruct(String),
+    MissingStructField {
+        struct_name: String,
+        field: String,
+    },
+    UnknownStructField {
+        struct_name: String,
+        field: String,
+    },
+    EmptyArrayLiteral,
+    ArrayElementTypeMismatch {
+        expected: String,
+        found: String,
+    },
+    UnsupportedUnaryOperand {
+        opcode: &'static str,
+        operand_type: String,
+    },
+    UnsupportedBinaryOperand {
+        opcode: &'static str,
+        operand_type: String,
+    },
+    UnsignedNegation(String),
+    /// A `pow` whose exponent Aleo does not take for its base.
+    InvalidExponent {
+        base_type: String,
+        exponent_type: String,
+    },
+    /// An integer type whose width Aleo has no type for.
+    UnsupportedIntegerWidth(String),
+    DynamicArrayIndex,
+    IndexOutOfBounds {
+        index: u128,
+        length: u32,
+    },
+    /// A slice type or method, described by the payload.
+    DynamicCollection(String),
+    UnknownMethod {
+        method: String,
+        known_methods: Vec<&'static str>,
+    },
+    ArgumentCountMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    UnknownOpcodeOverride {
+        opcode: String,
+        overridable: Vec<&'static str>,
+    },
+    InvalidRecordField {
+        record: String,
+        field: String,
+        expected: &'static str,
+        found: String,
+    },
+    InvalidMapping {
+        name: String,
+        reason: &'static str,
+    },
+    InvalidIdentifier {
+        kind: &'static str,
+        name: String,
+        reason: &'static str,
+    },
+    InvalidFinalize {
+        function: String,
+        reason: &'static str,
+    },
+    /// Structs containing one another, from the first back to itself.
+    RecursiveStruct(Vec<String>),
+    InvalidStructMember {
+        struct_name: String,
+        field: String,
+        reason: &'static str,
+    },
+    /// A parameter named by the `#[private(..)]` attribute of `function`
+    /// that cannot be made private.
+    InvalidVisibilityOverride {
+        function: String,
+        parameter: String,
+        reason: &'static str,
+    },
+    /// A division or remainder by a literal zero in `function`.
+    DivisionByZero(String),
+    /// A program with nothing but types, in a dialect that needs a function.
+    NoFunctions,
+    /// An error caused by the code at `span` of the root file.
+    Spanned {
+        error: Box<CompileError>,
+        span: Span,
+    },
+    /// An error whose span has been resolved to a position in a file.
+    Located {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        error: Box<CompileError>,
+    },
+    /// A bug of the compiler, caught by the check of the emitted program
+    /// called `rule`.
+    Internal {
+        rule: &'static str,
+        message: String,
+    },
+    /// Every error found by a build that kept going after the first one.
+    Unsupported(Vec<Diagnostic>),
+}
+
+/// An error recorded while compiling `function`, instead of stopping there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub function: String,
+    pub error: CompileError,
+}
+
+impl CompileError {
+    /// Attaches `span` to the error, unless it already points at a narrower
+    /// range of the source.
+    pub fn with_span(self, span: Span) -> Self {
+        match self {
+            CompileError::Spanned { .. } | CompileError::Located { .. } => self,
+            error => CompileError::Spanned {
+                error: Box::new(error),
+                span,
+            },
+        }
+    }
+
+    fn span(&self) -> Option<Span> {
+        match self {
+            CompileError::Spanned { span, .. } | CompileError::NonLiteralArrayLength(span) => {
+                Some(*span)
+            }
+            CompileError::Parse(error) => Some(error.span),
+            _ => None,
+        }
+    }
+
+    /// Turns the span of the error into a line and column of `source`, the
+    /// contents of `path`.
+    pub fn locate(self, path: &Path, source: &str) -> Self {
+        let Some(span) = self.span() else {
+            return self;
+        };
+        let before = &source[..span.start.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        CompileError::Located {
+            path: path.to_owned(),
+            line,
+            column: before[line_start..].chars().count() + 1,
+            error: Box::new(self.without_location()),
+        }
+    }
+
+    /// The error without the span or position attached to it.
+    pub fn without_location(self) -> Self {
+        match self {
+            CompileError::Spanned { error, .. } | CompileError::Located { error, .. } => {
+                error.without_location()
+            }
+            CompileError::Unsupported(diagnostics) => CompileError::Unsupported(
+                diagnostics
+                    .into_iter()
+                    .map(|diagnostic| Diagnostic {
+                        error: diagnostic.error.without_location(),
+                        ..diagnostic
+                    })
+                    .collect(),
+            ),
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::MissingManifest(path) => {
+                write!(f, "cannot find a Nargo.toml in {}", path.display())
+            }
+            CompileError::InvalidManifest { path, message } => {
+                write!(f, "invalid manifest {}: {message}", path.display())
+            }
+            CompileError::UnsupportedDependencies => {
+                write!(f, "dependencies are not supported yet")
+            }
+            CompileError::Io { path, message } => write!(f, "{}: {message}", path.display()),
+            CompileError::Parse(error) => write!(f, "parse error: {error}"),
+            CompileError::UnsupportedExpression(kind) => {
+                write!(f, "{kind} expressions are not supported yet")
+            }
+            CompileError::UnsupportedType(typ) => {
+                write!(f, "the type `{typ}` is not supported yet")
+            }
+            CompileError::UnsupportedStatement(kind) => {
+                write!(f, "{kind} statements are not supported yet")
+            }
+            CompileError::UnsupportedPattern(kind) => {
+                write!(f, "{kind} patterns are not supported here")
+            }
+            CompileError::UndeclaredVariable(name) => {
+                write!(f, "cannot find value `{name}` in this scope")
+            }
+            CompileError::UsedBeforeBinding(name) => {
+                write!(f, "`{name}` is used before the `let` binding it")
+            }
+            CompileError::CyclicGlobal(name) => {
+                write!(f, "the value of global `{name}` depends on itself")
+            }
+            CompileError::FallibleBranch => write!(
+                f,
+                "an `if` statement cannot compute an operation that can fail, since it is \
+                 computed whether the condition holds or not"
+            ),
+            CompileError::IntegerLiteralOutOfRange { value, typ } => {
+                write!(f, "integer literal `{value}` does not fit in `{typ}`")
+            }
+            CompileError::TypeMismatch { expected, found } => {
+                write!(
+                    f,
+                    "mismatched types, expected `{expected}`, found `{found}`"
+                )
+            }
+            CompileError::MissingReturnValue {
+                function,
+                return_type,
+            } => write!(
+                f,
+                "function `{function}` has an empty body but must return a value of type `{return_type}`"
+            ),
+            CompileError::UnsupportedCast { from, to } => {
+                write!(f, "cannot cast `{from}` into `{to}` in Aleo")
+            }
+            CompileError::TupleArityMismatch { expected, found } => {
+                write!(f, "expected a tuple of {expected} elements, found {found}")
+            }
+            CompileError::UndeclaredFunction(name) => {
+                write!(f, "cannot find function `{name}` in this program")
+            }
+            CompileError::UnsupportedExternalCall(path) => write!(
+                f,
+                "cannot call `{path}`: calls into other programs are not supported yet"
+            ),
+            CompileError::NoAleoEquivalent { path, reason } => {
+                write!(
+                    f,
+                    "`{path}` has no Aleo equivalent, and standard library functions are not compiled from Noir"
+                )?;
+                match reason {
+                    Some(reason) => write!(f, "\n\n{reason}"),
+                    None => Ok(()),
+                }
+            }
+            CompileError::InvalidIntrinsic { path, reason } => {
+                write!(f, "invalid intrinsic `{path}`: {reason}")
+            }
+            CompileError::InvalidIntrinsicArgument {
+                intrinsic,
+                found,
+                accepted,
+            } => write!(
+                f,
+                "`{intrinsic}` takes one of {}, found {found}",
+                accepted.join(", ")
+            ),
+            CompileError::NonLiteralArrayLength(_) => {
+                write!(f, "array lengths in signatures must be integer literals")
+            }
+            CompileError::UndeclaredStruct(name) => {
+                write!(f, "cannot find struct `{name}` in this program")
+            }
+            CompileError::MissingStructField { struct_name, field } => {
+                write!(
+                    f,
+                    "missing field `{field}` in constructor of `{struct_name}`"
+                )
+            }
+            CompileError::UnknownStructField { struct_name, field } => {
+                write!(f, "struct `{struct_name}` has no field named `{field}`")
+            }
+            CompileError::EmptyArrayLiteral => {
+                write!(f, "Aleo arrays must have at least one element")
+            }
+            CompileError::ArrayElementTypeMismatch { expected, found } => write!(
+                f,
+                "array elements must all have the same type, expected `{expected}`, found `{found}`"
+            ),
+            CompileError::UnsupportedUnaryOperand {
+                opcode,
+                operand_type,
+            } => write!(f, "cannot apply `{opcode}` to a `{operand_type}`"),
+            CompileError::UnsupportedBinaryOperand {
+                opcode,
+                operand_type,
+            } => write!(f, "cannot apply `{opcode}` to `{operand_type}` operands"),
+            CompileError::InvalidExponent {
+                base_type,
+                exponent_type,
+            } => write!(
+                f,
+                "cannot raise a `{base_type}` to a `{exponent_type}` power: Aleo takes `u8`, `u16` \
+                 or `u32` exponents for integers and `field` exponents for fields"
+            ),
+            CompileError::UnsignedNegation(operand_type) => write!(
+                f,
+                "cannot negate a `{operand_type}`: Aleo's `neg` is only defined on fields, \
+                 groups and signed integers, subtract from zero if wrapping is intended"
+            ),
+            CompileError::UnsupportedIntegerWidth(typ) => write!(
+                f,
+                "Aleo has no `{typ}` type, integers must be 8, 16, 32, 64 or 128 bits wide"
+            ),
+            CompileError::DynamicArrayIndex => write!(
+                f,
+                "dynamic array indexing is not supported on Aleo, index with an integer literal"
+            ),
+            CompileError::IndexOutOfBounds { index, length } => write!(
+                f,
+                "index {index} is out of bounds for an array of length {length}"
+            ),
+            CompileError::DynamicCollection(construct) => write!(
+                f,
+                "{construct} needs a dynamically sized collection, which Aleo does not have. \
+                 Use a fixed-size array with a separate length instead, for example:\n\n\
+                 \x20   let mut items: [u32; 4] = [0; 4];\n\
+                 \x20   let mut len: u32 = 0;\n\
+                 \x20   items[0] = x;\n\
+                 \x20   len = 1;\n\n\
+                 A `let mut` slice that is only pushed to can also be lowered to such an array \
+                 with `--bounded-vec N`."
+            ),
+            CompileError::UnknownMethod {
+                method,
+                known_methods,
+            } => write!(
+                f,
+                "unknown method `{method}`, expected one of: {}",
+                known_methods.join(", ")
+            ),
+            CompileError::ArgumentCountMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{name}` takes {expected} arguments but {found} were given"
+            ),
+            CompileError::UnknownOpcodeOverride {
+                opcode,
+                overridable,
+            } => write!(
+                f,
+                "cannot override the opcode `{opcode}`, expected one of: {}",
+                overridable.join(", ")
+            ),
+            CompileError::InvalidRecordField {
+                record,
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "the `{field}` field of record `{record}` must be a `{expected}`, found `{found}`"
+            ),
+            CompileError::InvalidMapping { name, reason } => {
+                write!(f, "invalid mapping `{name}`: {reason}")
+            }
+            CompileError::InvalidIdentifier { kind, name, reason } => {
+                write!(f, "`{name}` cannot be used as a {kind} name in Aleo: {reason}")
+            }
+            CompileError::InvalidFinalize { function, reason } => {
+                write!(f, "invalid finalize in `{function}`: {reason}")
+            }
+            CompileError::RecursiveStruct(cycle) => write!(
+                f,
+                "struct `{}` contains itself through {}, which Aleo cannot lay out",
+                cycle[0],
+                cycle.join(" -> ")
+            ),
+            CompileError::InvalidStructMember {
+                struct_name,
+                field,
+                reason,
+            } => write!(f, "invalid field `{field}` of `{struct_name}`: {reason}"),
+            CompileError::InvalidVisibilityOverride {
+                function,
+                parameter,
+                reason,
+            } => write!(
+                f,
+                "cannot keep `{parameter}` of `{function}` private: {reason}"
+            ),
+            CompileError::DivisionByZero(function) => {
+                write!(f, "`{function}` divides by zero, which always fails")
+            }
+            CompileError::NoFunctions => write!(
+                f,
+                "the program defines no functions, which snarkVM cannot deploy. \
+                 Add a function, or set `dialect = \"interface\"` under `[aleo]` \
+                 in Nargo.toml to emit only its types"
+            ),
+            CompileError::Spanned { error, .. } => write!(f, "{error}"),
+            CompileError::Located {
+                path,
+                line,
+                column,
+                error,
+            } => write!(f, "{}:{line}:{column}: {error}", path.display()),
+            CompileError::Internal { rule, message } => {
+                write!(f, "internal compiler error [{rule}]: {message}")
+            }
+            CompileError::Unsupported(diagnostics) => {
+                let mut functions: Vec<&str> = diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.function.as_str())
+                    .collect();
+                functions.sort_unstable();
+                functions.dedup();
+                let plural = |count: usize| if count == 1 { "" } else { "s" };
+                write!(
+                    f,
+                    "{} unsupported construct{} in {} function{}",
+                    diagnostics.len(),
+                    plural(diagnostics.len()),
+                    functions.len(),
+                    plural(functions.len())
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
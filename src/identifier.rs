@@ -0,0 +1,70 @@
+//! The rules Aleo places on the identifiers of a program.
+
+use crate::error::CompileError;
+
+/// Aleo identifiers are stored in a single field element.
+const MAX_IDENTIFIER_LENGTH: usize = 31;
+
+/// Words with a meaning of their own in Aleo instructions.
+const RESERVED_WORDS: &[&str] = &[
+    "program",
+    "import",
+    "function",
+    "closure",
+    "finalize",
+    "struct",
+    "record",
+    "mapping",
+    "input",
+    "output",
+    "key",
+    "value",
+    "as",
+    "into",
+    "self",
+    "true",
+    "false",
+    "address",
+    "boolean",
+    "field",
+    "group",
+    "scalar",
+    "signature",
+    "string",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+];
+
+/// Checks that `name` can be used as the identifier of a `kind`, such as a
+/// function, in an Aleo program.
+pub fn validate(kind: &'static str, name: &str) -> Result<(), CompileError> {
+    let reason = if name.is_empty() {
+        "it is empty"
+    } else if !name
+        .bytes()
+        .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+    {
+        "it may only contain ASCII letters, digits and underscores"
+    } else if !name.as_bytes()[0].is_ascii_alphabetic() {
+        "it must start with a letter"
+    } else if name.len() > MAX_IDENTIFIER_LENGTH {
+        "it is longer than 31 bytes"
+    } else if RESERVED_WORDS.contains(&name) {
+        "it is a reserved word in Aleo"
+    } else {
+        return Ok(());
+    };
+    Err(CompileError::InvalidIdentifier {
+        kind,
+        name: name.to_owned(),
+        reason,
+    })
+}
@@ -0,0 +1,480 @@
+//! The compiled program before it is written as text: its top-level items
+//! and, for callables, their inputs, instructions and outputs. The `emit`
+//! module is the one place that decides how they are laid out.
+
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use noir_parser::Span;
+
+use crate::{CallableKind, Visibility};
+
+/// An Aleo program: its name and its structs, records, mappings and
+/// callables, along with what the validator needs to know about its
+/// callables.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AleoProgram {
+    pub name: String,
+    pub items: Vec<Item>,
+    /// The names of the records the program defines.
+    pub records: Vec<String>,
+    pub signatures: IndexMap<String, Signature>,
+    pub call_sites: Vec<CallSite>,
+}
+
+/// What a callable of the program accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub kind: CallableKind,
+    /// The type of each input, unknown for those that failed to compile.
+    pub inputs: Vec<Option<String>>,
+}
+
+/// A `call` instruction emitted for the Noir call at `span`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallSite {
+    pub caller: String,
+    pub callee: String,
+    /// The type of each operand, where it is known.
+    pub operand_types: Vec<Option<String>>,
+    pub span: Span,
+}
+
+impl AleoProgram {
+    pub fn new(name: impl Into<String>) -> Self {
+        AleoProgram {
+            name: name.into(),
+            ..AleoProgram::default()
+        }
+    }
+
+    pub fn push_item(&mut self, item: Item) {
+        self.items.push(item);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Struct(Composite),
+    Record(Composite),
+    Mapping(Mapping),
+    Function(AleoFunction),
+}
+
+/// A struct or record, with the Aleo type of each member. Record members are
+/// all private.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Composite {
+    pub name: String,
+    pub members: Vec<(String, String)>,
+}
+
+/// A mapping, whose keys and values are always public.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    pub name: String,
+    pub key_type: String,
+    pub value_type: String,
+}
+
+/// A transition, function, closure or finalize block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AleoFunction {
+    pub kind: CallableKind,
+    pub name: String,
+    pub inputs: Vec<Input>,
+    pub instructions: Vec<Instruction>,
+    pub outputs: Vec<Output>,
+    /// The operands passed on-chain and the block receiving them.
+    pub finalize: Option<(Vec<Operand>, Box<AleoFunction>)>,
+    /// Where the divisions computing these registers are in the source.
+    pub division_spans: HashMap<Register, Span>,
+}
+
+impl AleoFunction {
+    pub fn new(kind: CallableKind, name: impl Into<String>) -> Self {
+        AleoFunction {
+            kind,
+            name: name.into(),
+            inputs: Vec::new(),
+            instructions: Vec::new(),
+            outputs: Vec::new(),
+            finalize: None,
+            division_spans: HashMap::new(),
+        }
+    }
+}
+
+/// An input, with no visibility in closures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    pub register: Register,
+    pub typ: String,
+    pub visibility: Option<Visibility>,
+}
+
+/// An output, with no visibility in closures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Output {
+    pub operand: Operand,
+    pub typ: String,
+    pub visibility: Option<Visibility>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Register(pub u64);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Register(Register),
+    /// An integer, field, group or scalar literal, such as `1u32`.
+    Integer {
+        value: u128,
+        typ: String,
+    },
+    Boolean(bool),
+    /// A member of a struct or record, such as `r0.x`.
+    Member {
+        base: Box<Operand>,
+        member: String,
+    },
+    /// An element of an array at a constant index, such as `r0[1u32]`.
+    Index {
+        base: Box<Operand>,
+        index: u128,
+    },
+}
+
+impl Operand {
+    /// The register the operand reads, if any.
+    pub fn register(&self) -> Option<Register> {
+        match self {
+            Operand::Register(register) => Some(*register),
+            Operand::Member { base, .. } | Operand::Index { base, .. } => base.register(),
+            Operand::Integer { .. } | Operand::Boolean(_) => None,
+        }
+    }
+
+    pub fn as_register(&self) -> Option<&Register> {
+        match self {
+            Operand::Register(register) => Some(register),
+            _ => None,
+        }
+    }
+
+    pub fn register_mut(&mut self) -> Option<&mut Register> {
+        match self {
+            Operand::Register(register) => Some(register),
+            Operand::Member { base, .. } | Operand::Index { base, .. } => base.register_mut(),
+            Operand::Integer { .. } | Operand::Boolean(_) => None,
+        }
+    }
+}
+
+impl From<Register> for Operand {
+    fn from(register: Register) -> Self {
+        Operand::Register(register)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    /// An instruction computing `destination` from its operands, such as
+    /// `add`, `not` or `ternary`.
+    Operation {
+        opcode: Opcode,
+        operands: Vec<Operand>,
+        destination: Register,
+    },
+    /// Builds a struct or array, or converts a value, into `typ` when it is
+    /// known.
+    Cast {
+        operands: Vec<Operand>,
+        destination: Register,
+        typ: Option<String>,
+    },
+    /// `assert.eq` or `assert.neq`.
+    Assert {
+        opcode: Opcode,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    /// `assert`, asserting that a boolean is true, in the dialects that have
+    /// it.
+    AssertTrue {
+        operand: Operand,
+    },
+    Call {
+        callee: String,
+        operands: Vec<Operand>,
+        destinations: Vec<Register>,
+    },
+    /// Reads `mapping[key]`, or `default` when the key is absent.
+    GetOrUse {
+        mapping: String,
+        key: Operand,
+        default: Operand,
+        destination: Register,
+    },
+    Contains {
+        mapping: String,
+        key: Operand,
+        destination: Register,
+    },
+    Set {
+        value: Operand,
+        mapping: String,
+        key: Operand,
+    },
+    Remove {
+        mapping: String,
+        key: Operand,
+    },
+    /// A hash or commitment of `operands` into `destination` as a value of
+    /// `typ`, such as `hash.psd2 r0 into r1 as field`.
+    Hash {
+        opcode: Opcode,
+        operands: Vec<Operand>,
+        destination: Register,
+        typ: String,
+    },
+}
+
+impl Instruction {
+    /// The operands the instruction reads.
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Instruction::Operation { operands, .. }
+            | Instruction::Cast { operands, .. }
+            | Instruction::Call { operands, .. }
+            | Instruction::Hash { operands, .. } => operands.iter_mut().collect(),
+            Instruction::Assert { lhs, rhs, .. } => vec![lhs, rhs],
+            Instruction::AssertTrue { operand } => vec![operand],
+            Instruction::GetOrUse { key, default, .. } => vec![key, default],
+            Instruction::Contains { key, .. } | Instruction::Remove { key, .. } => vec![key],
+            Instruction::Set { value, key, .. } => vec![value, key],
+        }
+    }
+
+    /// The registers the instruction writes.
+    pub fn destinations_mut(&mut self) -> Vec<&mut Register> {
+        match self {
+            Instruction::Operation { destination, .. }
+            | Instruction::Cast { destination, .. }
+            | Instruction::GetOrUse { destination, .. }
+            | Instruction::Contains { destination, .. }
+            | Instruction::Hash { destination, .. } => vec![destination],
+            Instruction::Call { destinations, .. } => destinations.iter_mut().collect(),
+            Instruction::Assert { .. }
+            | Instruction::AssertTrue { .. }
+            | Instruction::Set { .. }
+            | Instruction::Remove { .. } => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    AddWrapped,
+    SubWrapped,
+    MulWrapped,
+    DivWrapped,
+    Mod,
+    IsEq,
+    IsNeq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+    Neg,
+    Abs,
+    AbsWrapped,
+    Not,
+    Pow,
+    PowWrapped,
+    Inv,
+    Square,
+    SquareRoot,
+    Double,
+    Ternary,
+    AssertEq,
+    AssertNeq,
+    HashBhp256,
+    HashBhp512,
+    HashBhp768,
+    HashBhp1024,
+    HashPed64,
+    HashPed128,
+    HashPsd2,
+    HashPsd4,
+    HashPsd8,
+    CommitBhp256,
+    CommitBhp512,
+    CommitBhp768,
+    CommitBhp1024,
+    CommitPed64,
+    CommitPed128,
+    SignVerify,
+    /// An opcode of a snarkVM fork, replacing one of the above through
+    /// `[aleo.opcode_overrides]`.
+    Custom(String),
+}
+
+impl Opcode {
+    /// The opcodes that have a name of their own.
+    const NAMED: &'static [Opcode] = &[
+        Opcode::Add,
+        Opcode::Sub,
+        Opcode::Mul,
+        Opcode::Div,
+        Opcode::AddWrapped,
+        Opcode::SubWrapped,
+        Opcode::MulWrapped,
+        Opcode::DivWrapped,
+        Opcode::Mod,
+        Opcode::IsEq,
+        Opcode::IsNeq,
+        Opcode::Lt,
+        Opcode::Lte,
+        Opcode::Gt,
+        Opcode::Gte,
+        Opcode::And,
+        Opcode::Or,
+        Opcode::Xor,
+        Opcode::Shl,
+        Opcode::Shr,
+        Opcode::Neg,
+        Opcode::Abs,
+        Opcode::AbsWrapped,
+        Opcode::Not,
+        Opcode::Pow,
+        Opcode::PowWrapped,
+        Opcode::Inv,
+        Opcode::Square,
+        Opcode::SquareRoot,
+        Opcode::Double,
+        Opcode::Ternary,
+        Opcode::AssertEq,
+        Opcode::AssertNeq,
+        Opcode::HashBhp256,
+        Opcode::HashBhp512,
+        Opcode::HashBhp768,
+        Opcode::HashBhp1024,
+        Opcode::HashPed64,
+        Opcode::HashPed128,
+        Opcode::HashPsd2,
+        Opcode::HashPsd4,
+        Opcode::HashPsd8,
+        Opcode::CommitBhp256,
+        Opcode::CommitBhp512,
+        Opcode::CommitBhp768,
+        Opcode::CommitBhp1024,
+        Opcode::CommitPed64,
+        Opcode::CommitPed128,
+        Opcode::SignVerify,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Sub => "sub",
+            Opcode::Mul => "mul",
+            Opcode::Div => "div",
+            Opcode::AddWrapped => "add.w",
+            Opcode::SubWrapped => "sub.w",
+            Opcode::MulWrapped => "mul.w",
+            Opcode::DivWrapped => "div.w",
+            Opcode::Mod => "mod",
+            Opcode::IsEq => "is.eq",
+            Opcode::IsNeq => "is.neq",
+            Opcode::Lt => "lt",
+            Opcode::Lte => "lte",
+            Opcode::Gt => "gt",
+            Opcode::Gte => "gte",
+            Opcode::And => "and",
+            Opcode::Or => "or",
+            Opcode::Xor => "xor",
+            Opcode::Shl => "shl",
+            Opcode::Shr => "shr",
+            Opcode::Neg => "neg",
+            Opcode::Abs => "abs",
+            Opcode::AbsWrapped => "abs.w",
+            Opcode::Not => "not",
+            Opcode::Pow => "pow",
+            Opcode::PowWrapped => "pow.w",
+            Opcode::Inv => "inv",
+            Opcode::Square => "square",
+            Opcode::SquareRoot => "square_root",
+            Opcode::Double => "double",
+            Opcode::Ternary => "ternary",
+            Opcode::AssertEq => "assert.eq",
+            Opcode::AssertNeq => "assert.neq",
+            Opcode::HashBhp256 => "hash.bhp256",
+            Opcode::HashBhp512 => "hash.bhp512",
+            Opcode::HashBhp768 => "hash.bhp768",
+            Opcode::HashBhp1024 => "hash.bhp1024",
+            Opcode::HashPed64 => "hash.ped64",
+            Opcode::HashPed128 => "hash.ped128",
+            Opcode::HashPsd2 => "hash.psd2",
+            Opcode::HashPsd4 => "hash.psd4",
+            Opcode::HashPsd8 => "hash.psd8",
+            Opcode::CommitBhp256 => "commit.bhp256",
+            Opcode::CommitBhp512 => "commit.bhp512",
+            Opcode::CommitBhp768 => "commit.bhp768",
+            Opcode::CommitBhp1024 => "commit.bhp1024",
+            Opcode::CommitPed64 => "commit.ped64",
+            Opcode::CommitPed128 => "commit.ped128",
+            Opcode::SignVerify => "sign.verify",
+            Opcode::Custom(name) => name,
+        }
+    }
+
+    /// The number of operands of a hash, commitment or signature opcode, the
+    /// opcodes intrinsics are compiled to.
+    pub fn intrinsic_arity(&self) -> Option<usize> {
+        match self {
+            Opcode::HashBhp256
+            | Opcode::HashBhp512
+            | Opcode::HashBhp768
+            | Opcode::HashBhp1024
+            | Opcode::HashPed64
+            | Opcode::HashPed128
+            | Opcode::HashPsd2
+            | Opcode::HashPsd4
+            | Opcode::HashPsd8 => Some(1),
+            Opcode::CommitBhp256
+            | Opcode::CommitBhp512
+            | Opcode::CommitBhp768
+            | Opcode::CommitBhp1024
+            | Opcode::CommitPed64
+            | Opcode::CommitPed128 => Some(2),
+            Opcode::SignVerify => Some(3),
+            _ => None,
+        }
+    }
+
+    /// Whether the instruction declares the type of its destination, as
+    /// hashes and commitments do.
+    pub fn declares_destination_type(&self) -> bool {
+        self.intrinsic_arity().is_some() && *self != Opcode::SignVerify
+    }
+
+    /// The opcode with the given name, taking any other name to be a custom
+    /// one.
+    pub fn from_name(name: &str) -> Self {
+        Opcode::NAMED
+            .iter()
+            .find(|opcode| opcode.as_str() == name)
+            .cloned()
+            .unwrap_or_else(|| Opcode::Custom(name.to_owned()))
+    }
+}
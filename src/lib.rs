@@ -0,0 +1,7378 @@
+//! Compiles Noir packages into Aleo instructions.
+//!
+//! [`compile_program`] compiles a package in memory and
+//! [`compile_to_aleo_instructions`] also writes the program and its metadata
+//! to disk, as the `noir_to_aleo_instructions` and `nargo-aleo` binaries do.
+//! [`compile_source`] compiles a root file that is not on disk.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ffi::OsString,
+    fs, io, mem,
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use indexmap::IndexMap;
+use noir_parser::{
+    AbiFEType, ArrayLiteral, AssignStatement, BinaryOpKind, BlockExpression, CallExpression,
+    ConstrainStatement, Distinctness, Expression, ExpressionKind, ForExpression,
+    FunctionDefinition, GlobalDefinition, Ident, IfExpression, InfixExpression, LValue, Literal,
+    MethodCallExpression, NoirStruct, ParsedModule, Pattern, Signedness, Span, Statement, UnaryOp,
+    UnresolvedType,
+};
+use serde::Deserialize;
+
+mod emit;
+mod error;
+mod identifier;
+mod ir;
+mod not_nargo;
+mod optimize;
+mod profile;
+mod reporter;
+mod test_vectors;
+mod validator;
+
+pub use error::{CompileError, Diagnostic};
+use ir::{
+    AleoFunction, AleoProgram, CallSite, Composite, Input, Instruction, Item, Mapping, Opcode,
+    Operand, Output, Register, Signature,
+};
+pub use profile::{Profile, ProfileEntry};
+use reporter::Reporter;
+pub use test_vectors::{SkippedTest, TestVector};
+
+const ALEO_BUILD_DIR: &str = "build/aleo";
+
+/// The bit widths of Aleo's integer types.
+const ALEO_INTEGER_WIDTHS: &[u32] = &[8, 16, 32, 64, 128];
+
+/// Built-in integer, field and group methods as `(method, Aleo opcode,
+/// arity, wrap mode)`, where the arity excludes the receiver.
+const BUILTIN_METHODS: &[(&str, &str, usize, WrapMode)] = &[
+    ("pow", "pow", 1, WrapMode::Checked),
+    ("pow_32", "pow", 1, WrapMode::Checked),
+    ("invert", "inv", 0, WrapMode::Checked),
+    ("square", "square", 0, WrapMode::Checked),
+    ("sqrt", "square_root", 0, WrapMode::Checked),
+    ("double", "double", 0, WrapMode::Checked),
+    ("abs", "abs", 0, WrapMode::Checked),
+    ("wrapping_add", "add", 1, WrapMode::Wrapping),
+    ("wrapping_sub", "sub", 1, WrapMode::Wrapping),
+    ("wrapping_mul", "mul", 1, WrapMode::Wrapping),
+    ("wrapping_div", "div", 1, WrapMode::Wrapping),
+    ("wrapping_abs", "abs", 0, WrapMode::Wrapping),
+    ("wrapping_pow", "pow", 1, WrapMode::Wrapping),
+];
+
+/// The functions of the standard library computing `lhs.<name>(rhs)`, which
+/// compile to the same instruction as the method.
+const WRAPPING_FUNCTIONS: &[&str] = &["wrapping_add", "wrapping_sub", "wrapping_mul"];
+
+/// What integer arithmetic does on overflow: checked arithmetic makes the
+/// program fail, while wrapping arithmetic computes the result modulo 2^N
+/// with the `.w` variant of the opcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WrapMode {
+    Checked,
+    Wrapping,
+}
+
+impl WrapMode {
+    /// The name of the opcode computing `opcode` in this mode.
+    fn opcode_name(self, opcode: &str) -> String {
+        match self {
+            WrapMode::Checked => opcode.to_owned(),
+            WrapMode::Wrapping => format!("{opcode}.w"),
+        }
+    }
+}
+
+/// The types whose bits fit the 64-bit input of `hash.ped64` and
+/// `commit.ped64`.
+const PED64_INPUT_TYPES: &[&str] = &[
+    "boolean", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64",
+];
+
+/// Standard library functions compiled to a single Aleo instruction rather
+/// than to their Noir implementation, as `(path, opcode, Aleo types each
+/// argument may have, result type)`. An argument with no types listed may
+/// have any type.
+const BUILTIN_INTRINSICS: &[(&str, Opcode, &[&[&str]], &str)] = &[
+    (
+        "std::hash::pedersen",
+        Opcode::HashPed64,
+        &[PED64_INPUT_TYPES],
+        "field",
+    ),
+    ("std::hash::poseidon", Opcode::HashPsd2, &[&[]], "field"),
+    ("std::hash::bhp256", Opcode::HashBhp256, &[&[]], "field"),
+    ("std::hash::bhp512", Opcode::HashBhp512, &[&[]], "field"),
+    ("std::hash::bhp768", Opcode::HashBhp768, &[&[]], "field"),
+    ("std::hash::bhp1024", Opcode::HashBhp1024, &[&[]], "field"),
+    (
+        "std::hash::pedersen_commitment",
+        Opcode::CommitPed64,
+        &[PED64_INPUT_TYPES, &["scalar"]],
+        "field",
+    ),
+    (
+        "std::hash::bhp256_commitment",
+        Opcode::CommitBhp256,
+        &[&[], &["scalar"]],
+        "field",
+    ),
+    (
+        "std::hash::bhp512_commitment",
+        Opcode::CommitBhp512,
+        &[&[], &["scalar"]],
+        "field",
+    ),
+    (
+        "std::hash::bhp768_commitment",
+        Opcode::CommitBhp768,
+        &[&[], &["scalar"]],
+        "field",
+    ),
+    (
+        "std::hash::bhp1024_commitment",
+        Opcode::CommitBhp1024,
+        &[&[], &["scalar"]],
+        "field",
+    ),
+    // Aleo verifies a signature against an address rather than against the
+    // coordinates of a public key.
+    (
+        "std::schnorr::verify_signature",
+        Opcode::SignVerify,
+        &[&["signature"], &["address"], &[]],
+        "boolean",
+    ),
+];
+
+/// A function compiled to a single Aleo instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Intrinsic {
+    opcode: Opcode,
+    /// The Aleo types each argument may have, any type when empty.
+    arguments: Vec<Vec<String>>,
+    result_type: String,
+}
+
+/// The intrinsics of a package: the built-in ones, and those its
+/// `aleo_intrinsics.toml` defines, which take precedence.
+#[derive(Debug, Clone, Default)]
+struct IntrinsicTable {
+    custom: BTreeMap<String, Intrinsic>,
+}
+
+impl IntrinsicTable {
+    /// Checks the entries of `file`, locating errors in it.
+    fn new(file: not_nargo::toml::IntrinsicsFile) -> Result<Self, CompileError> {
+        let mut custom = BTreeMap::new();
+        for (noir_path, entry) in file.entries {
+            let invalid = |reason: String, span: std::ops::Range<usize>| {
+                CompileError::InvalidIntrinsic {
+                    path: noir_path.clone(),
+                    reason,
+                }
+                .with_span(Span::new(span.start, span.end))
+                .locate(&file.path, &file.contents)
+            };
+            let opcode = Opcode::from_name(entry.opcode.get_ref());
+            let Some(arity) = opcode.intrinsic_arity() else {
+                return Err(invalid(
+                    format!("`{opcode}` is not a hash, commitment or signature opcode"),
+                    entry.opcode.span(),
+                ));
+            };
+            if entry.arguments.get_ref().len() != arity {
+                return Err(invalid(
+                    format!(
+                        "`{opcode}` takes {arity} operands but {} arguments are listed",
+                        entry.arguments.get_ref().len()
+                    ),
+                    entry.arguments.span(),
+                ));
+            }
+            let intrinsic = Intrinsic {
+                opcode,
+                arguments: entry
+                    .arguments
+                    .into_inner()
+                    .into_iter()
+                    .map(|typ| vec![typ])
+                    .collect(),
+                result_type: entry.result,
+            };
+            custom.insert(noir_path, intrinsic);
+        }
+        Ok(IntrinsicTable { custom })
+    }
+
+    /// The intrinsic called through `path`, if any.
+    fn get(&self, path: &str) -> Option<Intrinsic> {
+        if let Some(intrinsic) = self.custom.get(path) {
+            return Some(intrinsic.clone());
+        }
+        let (_, opcode, arguments, result_type) = BUILTIN_INTRINSICS
+            .iter()
+            .find(|(builtin_path, _, _, _)| *builtin_path == path)?;
+        Some(Intrinsic {
+            opcode: opcode.clone(),
+            arguments: arguments
+                .iter()
+                .map(|types| types.iter().map(|typ| (*typ).to_owned()).collect())
+                .collect(),
+            result_type: (*result_type).to_owned(),
+        })
+    }
+}
+
+/// Standard library functions that look like they have an Aleo instruction
+/// but do not, with why, as `(path, reason)`.
+const UNMAPPED_INTRINSICS: &[(&str, &str)] = &[(
+    "std::hash::poseidon2",
+    "Aleo's `hash.psd2` computes Poseidon, not Poseidon2. A package targeting a snarkVM fork \
+     that has Poseidon2 can map it to an instruction in `aleo_intrinsics.toml`.",
+)];
+
+/// Aleo types that Noir has no primitive for, written in Noir as a named type
+/// and mapped as `(Noir name, Aleo type)`.
+const ALEO_NATIVE_TYPES: &[(&str, &str)] = &[
+    ("Group", "group"),
+    ("Scalar", "scalar"),
+    ("Address", "address"),
+    ("Signature", "signature"),
+];
+
+/// The opcodes a package may replace through `[aleo.opcode_overrides]`: those
+/// of binary operators and built-in methods.
+const OVERRIDABLE_OPCODES: &[&str] = &[
+    "add",
+    "sub",
+    "mul",
+    "div",
+    "is.eq",
+    "is.neq",
+    "lt",
+    "lte",
+    "gt",
+    "gte",
+    "and",
+    "or",
+    "xor",
+    "shr",
+    "shl",
+    "mod",
+    "pow",
+    "inv",
+    "square",
+    "square_root",
+    "double",
+    "abs",
+];
+
+/// The fields Aleo requires at the start of every record, with their types.
+const RECORD_OWNERSHIP_FIELDS: &[(&str, &str)] = &[("owner", "address"), ("gates", "u64")];
+
+/// Methods of mappings as `(method, arity)`.
+const MAPPING_METHODS: &[(&str, usize)] = &[("get", 1), ("set", 2), ("contains", 1), ("remove", 1)];
+
+/// Methods of Noir's dynamically sized collections, which have no Aleo
+/// counterpart.
+const DYNAMIC_COLLECTION_METHODS: &[&str] = &[
+    "push",
+    "push_back",
+    "push_front",
+    "pop",
+    "pop_back",
+    "pop_front",
+    "insert",
+    "remove",
+    "len",
+];
+
+/// The methods of a slice lowered to a bounded array, see [`BoundedVec`].
+const BOUNDED_VEC_METHODS: &[&str] = &["push", "push_back", "len"];
+
+/// The structs declared in the program, by name.
+type StructRegistry<'a> = IndexMap<String, &'a NoirStruct>;
+
+/// A `#[mapping(K)] global name: V = default;` declaration, which becomes an
+/// Aleo mapping from `K` to `V` whose reads fall back to `default`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AleoMapping {
+    key_type: String,
+    value_type: String,
+    default: Operand,
+}
+
+/// The mappings declared in the program, by name.
+type MappingRegistry = IndexMap<String, AleoMapping>;
+
+/// The kinds of callable blocks of an Aleo program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallableKind {
+    /// An entry point of the program.
+    Transition,
+    /// A helper called by other functions.
+    Function,
+    /// A helper inlined into its callers, whose operands have no visibility.
+    Closure,
+    /// The on-chain part of a transition.
+    Finalize,
+}
+
+/// The opcodes to emit, with the package's overrides applied.
+#[derive(Debug, Clone, Default)]
+struct OpcodeTable {
+    overrides: BTreeMap<String, String>,
+}
+
+impl OpcodeTable {
+    fn new(overrides: &BTreeMap<String, String>) -> Result<Self, CompileError> {
+        if let Some(opcode) = overrides
+            .keys()
+            .find(|opcode| !OVERRIDABLE_OPCODES.contains(&opcode.as_str()))
+        {
+            return Err(CompileError::UnknownOpcodeOverride {
+                opcode: opcode.clone(),
+                overridable: OVERRIDABLE_OPCODES.to_vec(),
+            });
+        }
+        Ok(OpcodeTable {
+            overrides: overrides.clone(),
+        })
+    }
+
+    fn get(&self, opcode: &str) -> Opcode {
+        Opcode::from_name(
+            self.overrides
+                .get(opcode)
+                .map(String::as_str)
+                .unwrap_or(opcode),
+        )
+    }
+}
+
+/// How the instructions of a program are emitted.
+#[derive(Debug, Clone, Default)]
+struct Target {
+    opcodes: OpcodeTable,
+    intrinsics: IntrinsicTable,
+    /// The width struct casts are kept within, if any.
+    max_line_width: Option<usize>,
+    /// The capacity `let mut` slices are lowered to, if any.
+    bounded_vec_capacity: Option<usize>,
+    dialect: Dialect,
+}
+
+/// Compilation state of the function currently being lowered.
+struct FunctionContext<'a> {
+    noir_ast: &'a ParsedModule,
+    struct_registry: &'a StructRegistry<'a>,
+    mappings: &'a MappingRegistry,
+    target: &'a Target,
+    kind: CallableKind,
+    /// What each name in scope is bound to.
+    register_registry: ScopeStack,
+    /// The names every `let` statement of the body binds, collected before it
+    /// is compiled.
+    let_bindings: Vec<LetBinding>,
+    /// The Aleo type of every operand whose type is known.
+    register_types: HashMap<Operand, String>,
+    register_count: u64,
+    function: AleoFunction,
+    /// The `#[finalize]` function called by this one, with its operands.
+    finalize: Option<(&'a FunctionDefinition, Vec<Operand>)>,
+    function_name: String,
+    call_sites: Vec<CallSite>,
+    /// The type given to unsuffixed integer literals, while compiling an
+    /// expression whose type comes from its surroundings.
+    literal_type: Option<String>,
+    /// The globals whose value is being compiled, innermost last.
+    expanding_globals: Vec<String>,
+    /// The structs the tuple values of the function are lowered to, with
+    /// the type of each element.
+    tuple_structs: IndexMap<String, Vec<String>>,
+    /// The slices lowered to bounded arrays, by name.
+    bounded_vecs: HashMap<String, BoundedVec>,
+    /// When the statements being compiled are in an `if`, the condition
+    /// under which they run.
+    condition: Option<Operand>,
+}
+
+/// A `let mut` slice that is only pushed to, lowered under `--bounded-vec N`
+/// to as many elements as its capacity and the number of them pushed so
+/// far. Each push writes every element, keeping the previous one unless it
+/// is the next free one, so that whether it happens can depend on values
+/// only known when the program runs.
+#[derive(Debug, Clone)]
+struct BoundedVec {
+    element_type: String,
+    elements: Vec<Operand>,
+    length: Operand,
+}
+
+/// The names bound in a function, one scope per block being compiled with
+/// the body of the function first. A name bound in a scope shadows the same
+/// name in the scopes around it until the scope is popped.
+struct ScopeStack {
+    scopes: Vec<IndexMap<String, RegisterInfo>>,
+}
+
+/// The operand a name is bound to, with its Aleo type when it is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RegisterInfo {
+    operand: Operand,
+    aleo_type: Option<String>,
+}
+
+impl ScopeStack {
+    fn new() -> Self {
+        ScopeStack {
+            scopes: vec![IndexMap::new()],
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(IndexMap::new());
+    }
+
+    /// Drops the innermost scope, along with every name bound in it.
+    fn pop_scope(&mut self) {
+        assert!(
+            self.scopes.len() > 1,
+            "the scope of the body is never popped"
+        );
+        self.scopes.pop();
+    }
+
+    /// Binds `name` in the innermost scope.
+    fn insert(&mut self, name: String, info: RegisterInfo) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, info);
+        }
+    }
+
+    /// What `name` is bound to in the innermost scope binding it.
+    fn get(&self, name: &str) -> Option<&RegisterInfo> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+}
+
+/// The state of a function to go back to when part of it fails to compile.
+struct Checkpoint {
+    register_count: u64,
+    input_count: usize,
+    instruction_count: usize,
+    call_site_count: usize,
+}
+
+impl<'a> FunctionContext<'a> {
+    fn new(
+        noir_ast: &'a ParsedModule,
+        struct_registry: &'a StructRegistry<'a>,
+        mappings: &'a MappingRegistry,
+        target: &'a Target,
+        kind: CallableKind,
+    ) -> Self {
+        FunctionContext {
+            noir_ast,
+            struct_registry,
+            mappings,
+            target,
+            kind,
+            register_registry: ScopeStack::new(),
+            let_bindings: Vec::new(),
+            register_types: HashMap::new(),
+            register_count: 0,
+            function: AleoFunction::new(kind, ""),
+            finalize: None,
+            function_name: String::new(),
+            call_sites: Vec::new(),
+            literal_type: None,
+            expanding_globals: Vec::new(),
+            tuple_structs: IndexMap::new(),
+            bounded_vecs: HashMap::new(),
+            condition: None,
+        }
+    }
+
+    /// The global constant `name`, mappings aside.
+    fn global(&self, name: &str) -> Option<&'a GlobalDefinition> {
+        self.noir_ast.globals.iter().find(|global| {
+            global.name.name == name
+                && !global
+                    .attributes
+                    .iter()
+                    .any(|attribute| attribute.name == "mapping")
+        })
+    }
+
+    /// Allocates the next register, recording its type if it is known.
+    fn new_register(&mut self, aleo_type: Option<String>) -> Register {
+        let register = Register(self.register_count);
+        self.register_count += 1;
+        if let Some(aleo_type) = aleo_type {
+            self.register_types.insert(register.into(), aleo_type);
+        }
+        register
+    }
+
+    /// Binds `name` to `operand`, with the type recorded for the operand.
+    fn bind(&mut self, name: String, operand: Operand) {
+        self.bounded_vecs.remove(&name);
+        let aleo_type = self.register_types.get(&operand).cloned();
+        self.register_registry
+            .insert(name, RegisterInfo { operand, aleo_type });
+    }
+
+    /// The operand `name` is bound to, with the type of the binding recorded
+    /// for it so that instructions reading it can be typed.
+    fn lookup(&mut self, name: &str) -> Option<Operand> {
+        let RegisterInfo { operand, aleo_type } = self.register_registry.get(name)?.clone();
+        if let Some(aleo_type) = aleo_type {
+            self.register_types
+                .entry(operand.clone())
+                .or_insert(aleo_type);
+        }
+        Some(operand)
+    }
+
+    /// The error for a name read at `span` that is not bound, which a `let`
+    /// further down the same block or an enclosing one may still bind.
+    fn unbound(&self, name: &str, span: Span) -> CompileError {
+        let is_bound_later = self.let_bindings.iter().any(|binding| {
+            binding.name == name
+                && binding.scope_start <= span.start
+                && binding.span.start > span.start
+        });
+        if is_bound_later {
+            CompileError::UsedBeforeBinding(name.to_owned())
+        } else {
+            CompileError::UndeclaredVariable(name.to_owned())
+        }
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            register_count: self.register_count,
+            input_count: self.function.inputs.len(),
+            instruction_count: self.function.instructions.len(),
+            call_site_count: self.call_sites.len(),
+        }
+    }
+
+    /// Discards the registers allocated and the lines emitted since
+    /// `checkpoint`.
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        for index in checkpoint.register_count..self.register_count {
+            self.register_types.remove(&Register(index).into());
+        }
+        self.register_count = checkpoint.register_count;
+        self.function.inputs.truncate(checkpoint.input_count);
+        self.function
+            .instructions
+            .truncate(checkpoint.instruction_count);
+        self.call_sites.truncate(checkpoint.call_site_count);
+    }
+
+    /// The visibility of an input or output, which closures do not have.
+    fn visibility(&self, visibility: &AbiFEType) -> Option<Visibility> {
+        match self.kind {
+            CallableKind::Closure => None,
+            _ => Some(to_aleo_visibility(visibility)),
+        }
+    }
+
+    /// The type inputs, outputs and casts declare a record named `aleo_type`
+    /// with, `Name.record`, which carries no visibility. Other types are
+    /// declared as themselves.
+    fn declared_type(&self, aleo_type: &str) -> Option<String> {
+        let noir_struct = self.struct_registry.get(aleo_type)?;
+        is_record(noir_struct).then(|| format!("{aleo_type}.record"))
+    }
+
+    fn push_instruction(&mut self, instruction: Instruction) {
+        self.function.instructions.push(instruction);
+    }
+
+    /// Builds a tuple of `elements` into a new register. Aleo has no tuple
+    /// type, so it is a struct named after the types of the elements, such
+    /// as `Tuple2_u32_field` for a `(u32, Field)`.
+    fn push_tuple(&mut self, elements: Vec<Operand>) -> Result<Operand, CompileError> {
+        let element_types = elements
+            .iter()
+            .map(|element| {
+                self.register_types.get(element).cloned().ok_or(
+                    CompileError::UnsupportedExpression("tuple of values of unknown types"),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let name = tuple_struct_name(&element_types);
+        identifier::validate("struct", &name)?;
+        self.tuple_structs.insert(name.clone(), element_types);
+        Ok(self.push_cast(elements, Some(name)))
+    }
+
+    /// The elements of `tuple`, if it holds one of `arity` elements.
+    fn tuple_elements(&self, tuple: &Operand, arity: usize) -> Result<Vec<Operand>, CompileError> {
+        let element_types = self
+            .register_types
+            .get(tuple)
+            .and_then(|aleo_type| self.tuple_structs.get(aleo_type))
+            .ok_or(CompileError::UnsupportedExpression("tuple-valued"))?;
+        if element_types.len() != arity {
+            return Err(CompileError::TupleArityMismatch {
+                expected: arity,
+                found: element_types.len(),
+            });
+        }
+        Ok((0..arity)
+            .map(|index| tuple_element(tuple, index))
+            .collect())
+    }
+
+    /// Emits `opcode` on `operands` into a new register of `result_type`.
+    fn push_operation(
+        &mut self,
+        opcode: Opcode,
+        operands: Vec<Operand>,
+        result_type: Option<String>,
+    ) -> Operand {
+        let destination = self.new_register(result_type);
+        self.push_instruction(Instruction::Operation {
+            opcode,
+            operands,
+            destination,
+        });
+        destination.into()
+    }
+
+    /// Emits a `cast` of `operands` into a new register of `typ`.
+    fn push_cast(&mut self, mut operands: Vec<Operand>, typ: Option<String>) -> Operand {
+        if let (Some(width), Some(struct_name)) = (self.target.max_line_width, &typ) {
+            if self.struct_registry.contains_key(struct_name) {
+                self.shorten_struct_cast(&mut operands, struct_name, width);
+            }
+        }
+        let destination = self.new_register(typ.clone());
+        let typ = typ.map(|typ| self.declared_type(&typ).unwrap_or(typ));
+        self.push_instruction(Instruction::Cast {
+            operands,
+            destination,
+            typ,
+        });
+        destination.into()
+    }
+
+    /// Moves the longest operands of a cast into `struct_name` to registers
+    /// of their own, each through a cast into its own type, until the cast
+    /// fits in `width` columns or no operand is left to move. Struct and
+    /// array operands stay, since building them again would take a cast
+    /// listing all their elements.
+    fn shorten_struct_cast(&mut self, operands: &mut [Operand], struct_name: &str, width: usize) {
+        loop {
+            let cast = Instruction::Cast {
+                operands: operands.to_vec(),
+                destination: Register(self.register_count),
+                typ: Some(struct_name.to_owned()),
+            };
+            if emit::line_width(&cast) <= width {
+                return;
+            }
+            let register_width = Register(self.register_count).to_string().len();
+            // The first of the longest operands, to keep the moves in order.
+            let Some((operand, typ)) = operands
+                .iter_mut()
+                .rev()
+                .filter_map(|operand| {
+                    let typ = self.register_types.get(operand)?;
+                    let movable = !matches!(operand, Operand::Register(_))
+                        && !typ.starts_with('[')
+                        && !self.struct_registry.contains_key(typ)
+                        && operand.to_string().len() > register_width;
+                    movable.then(|| (operand, typ.clone()))
+                })
+                .max_by_key(|(operand, _)| operand.to_string().len())
+            else {
+                return;
+            };
+            let destination = self.new_register(Some(typ.clone()));
+            self.push_instruction(Instruction::Cast {
+                operands: vec![operand.clone()],
+                destination,
+                typ: Some(typ),
+            });
+            *operand = destination.into();
+        }
+    }
+}
+
+/// Compiles a Noir package into Aleo instructions.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Print nothing but the result and errors.
+    #[arg(long, short, global = true)]
+    quiet: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Compile a package and write the Aleo program to the output directory.
+    Build(BuildArgs),
+    /// Report every construct of a package that cannot be compiled, without
+    /// writing anything.
+    Check(CheckArgs),
+}
+
+#[derive(Debug, Args)]
+struct BuildArgs {
+    /// Directory of the Noir package to compile.
+    #[arg(default_value = ".")]
+    program_dir: PathBuf,
+    /// Directory the Aleo program is written to.
+    #[arg(long, default_value = ALEO_BUILD_DIR)]
+    output_dir: PathBuf,
+    /// Name of the Aleo program, instead of the one derived from the package.
+    #[arg(long)]
+    program_name: Option<String>,
+    /// Print the generated program to stdout instead of writing any file.
+    #[arg(long)]
+    print: bool,
+    /// Also print the generated program to stdout, instead of the path it
+    /// is written to.
+    #[arg(long, conflicts_with_all = ["print", "emit_ir"])]
+    verbose: bool,
+    /// Print the intermediate representation of the program to stdout,
+    /// before it is written as text, instead of writing any file.
+    #[arg(long, conflicts_with = "print")]
+    emit_ir: bool,
+    /// Reuse the result of each computation instead of repeating it.
+    #[arg(long)]
+    optimize: bool,
+    /// Lower the `let mut` slices that are only pushed to into arrays of
+    /// `N` elements, instead of rejecting them.
+    #[arg(long, value_name = "N")]
+    bounded_vec: Option<usize>,
+    /// Print how long each phase took and write them to `profile.json` in
+    /// the output directory, with the peak memory of each phase when built
+    /// with the `alloc-profile` feature.
+    #[arg(long)]
+    profile: bool,
+    /// Extra artifacts to write to the output directory.
+    #[arg(long, value_enum)]
+    emit: Vec<Emit>,
+    /// Visibility of the parameters declared without `pub`, instead of the
+    /// `default_visibility` of the package.
+    #[arg(long, value_enum)]
+    default_visibility: Option<Visibility>,
+}
+
+#[derive(Debug, Args)]
+struct CheckArgs {
+    /// Directory of the Noir package to check.
+    #[arg(default_value = ".")]
+    program_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Emit {
+    /// One `tests/<name>.json` execution fixture per `#[test]` function
+    /// that calls the program with literal arguments.
+    TestVectors,
+}
+
+/// Runs the command line with `args`, the first of which is the name of the
+/// binary, and exits with status 1 on error.
+pub fn run(args: impl IntoIterator<Item = OsString>) {
+    let cli = Cli::parse_from(args);
+    let reporter = Reporter { quiet: cli.quiet };
+    let result = match cli.command {
+        Command::Build(args) => build(args, &reporter),
+        Command::Check(args) => check(args, &reporter),
+    };
+    if let Err(error) = result {
+        reporter.error(&error);
+        process::exit(1);
+    }
+}
+
+/// Runs the command line as `nargo aleo`. nargo passes the name of the
+/// subcommand before its arguments, and packages are found the way nargo
+/// finds them: from `--program-dir` or the current directory, going up to the
+/// closest directory with a `Nargo.toml`. Build outputs then go to the package
+/// rather than the current directory.
+pub fn run_as_nargo_subcommand(args: impl IntoIterator<Item = OsString>) {
+    let args: Vec<OsString> = args.into_iter().collect();
+    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    match nargo_subcommand_args(args, &current_dir) {
+        Ok(args) => run(args),
+        Err(error) => {
+            Reporter::default().error(&error);
+            process::exit(1);
+        }
+    }
+}
+
+/// Rewrites the arguments nargo passes to `nargo-aleo` into those of `run`.
+fn nargo_subcommand_args(
+    args: Vec<OsString>,
+    current_dir: &Path,
+) -> Result<Vec<OsString>, CompileError> {
+    let mut args = args.into_iter();
+    let mut rewritten: Vec<OsString> = args.next().into_iter().collect();
+    let mut args: Vec<OsString> = args.collect();
+    if args.first().is_some_and(|arg| arg == "aleo") {
+        args.remove(0);
+    }
+    let Some(command) = args
+        .first()
+        .filter(|arg| *arg == "build" || *arg == "check")
+    else {
+        rewritten.extend(args);
+        return Ok(rewritten);
+    };
+    let is_build = command == "build";
+    rewritten.push(command.clone());
+    let mut program_dir = current_dir.to_owned();
+    let mut has_output_dir = false;
+    let mut rest = Vec::new();
+    let mut remaining = args.into_iter().skip(1);
+    while let Some(arg) = remaining.next() {
+        let text = arg.to_string_lossy();
+        if text == "--program-dir" {
+            if let Some(dir) = remaining.next() {
+                program_dir = current_dir.join(dir);
+            }
+        } else if let Some(dir) = text.strip_prefix("--program-dir=") {
+            program_dir = current_dir.join(dir);
+        } else {
+            has_output_dir |= text == "--output-dir" || text.starts_with("--output-dir=");
+            rest.push(arg);
+        }
+    }
+    let package_root = not_nargo::find_package_root(&program_dir)?;
+    if is_build && !has_output_dir {
+        rest.push("--output-dir".into());
+        rest.push(package_root.join(ALEO_BUILD_DIR).into());
+    }
+    rewritten.push(package_root.into());
+    rewritten.extend(rest);
+    Ok(rewritten)
+}
+
+fn build(args: BuildArgs, reporter: &Reporter) -> Result<(), CompileError> {
+    let options = CompileOptions {
+        program_name: args.program_name,
+        emit_test_vectors: args.emit.contains(&Emit::TestVectors),
+        collect_diagnostics: false,
+        default_visibility: args.default_visibility,
+        dialect: None,
+        max_line_width: None,
+        optimize: args.optimize,
+        bounded_vec: args.bounded_vec,
+    };
+    let output_config = OutputConfig {
+        output_dir: args.output_dir,
+    };
+    let writes_files = !args.print && !args.emit_ir;
+    reporter.status(&format!("Compiling {}", args.program_dir.display()));
+    let compiled_program = compile_to_aleo_instructions(
+        &args.program_dir,
+        &options,
+        writes_files.then_some(&output_config),
+    )?;
+    for warning in &compiled_program.warnings {
+        reporter.warning(warning);
+    }
+    for skipped_test in &compiled_program.skipped_tests {
+        reporter.note(&format!(
+            "no test vector for `{}`: {}",
+            skipped_test.name, skipped_test.reason
+        ));
+    }
+    if args.profile {
+        reporter.status(compiled_program.profile.to_table().trim_end());
+        if writes_files {
+            let profile_path = output_config.output_dir.join("profile.json");
+            fs::write(&profile_path, compiled_program.profile.to_json()).map_err(|error| {
+                CompileError::Io {
+                    path: profile_path,
+                    message: error.to_string(),
+                }
+            })?;
+        }
+    }
+    if args.print || args.verbose {
+        reporter.result(&compiled_program.aleo_program);
+    } else if args.emit_ir {
+        reporter.result(&format!("{:#?}", compiled_program.ir));
+    } else {
+        let path = output_config.output_dir.join(compiled_program.file_name());
+        reporter.result(&path.display().to_string());
+    }
+    Ok(())
+}
+
+/// Runs the whole compilation of `build` in memory, so that the two cannot
+/// disagree on what compiles.
+fn check(args: CheckArgs, reporter: &Reporter) -> Result<(), CompileError> {
+    let options = CompileOptions {
+        collect_diagnostics: true,
+        ..CompileOptions::default()
+    };
+    reporter.status(&format!("Checking {}", args.program_dir.display()));
+    compile_program(&args.program_dir, &options)?;
+    reporter.result("ok");
+    Ok(())
+}
+
+/// A package that has been located on disk and parsed, as returned by
+/// [`resolve`] and compiled by [`compile`].
+pub struct ResolvedPackage {
+    noir_ast: ParsedModule,
+    name: String,
+    root_file: PathBuf,
+    source: String,
+    description: Option<String>,
+    opcode_overrides: BTreeMap<String, String>,
+    default_visibility: Option<Visibility>,
+    dialect: Option<Dialect>,
+    max_line_width: Option<usize>,
+    bounded_vec: Option<usize>,
+    intrinsics: IntrinsicTable,
+}
+
+impl ResolvedPackage {
+    /// The name of the package, which the program is named after by default.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The path of the package's root file, which errors point into.
+    pub fn root_file(&self) -> &Path {
+        &self.root_file
+    }
+}
+
+/// Settings that affect how a package is lowered to Aleo.
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    /// Overrides the program name derived from the package.
+    pub program_name: Option<String>,
+    /// Extracts test vectors from the `#[test]` functions of the package.
+    pub emit_test_vectors: bool,
+    /// Keeps compiling after an error, to report all of them at once as
+    /// [`CompileError::Unsupported`].
+    pub collect_diagnostics: bool,
+    /// Overrides the `default_visibility` of the package.
+    pub default_visibility: Option<Visibility>,
+    /// Overrides the `dialect` of the package.
+    pub dialect: Option<Dialect>,
+    /// Overrides the `max_line_width` of the package.
+    pub max_line_width: Option<usize>,
+    /// Runs the optimizations that are off by default: reusing the result
+    /// of a computation instead of repeating it.
+    pub optimize: bool,
+    /// Overrides the `bounded_vec` capacity of the package.
+    pub bounded_vec: Option<usize>,
+}
+
+/// The visibility of the inputs of transitions and functions declared
+/// without `pub`, private unless configured otherwise. A function lists the
+/// parameters that stay private whatever the default in a
+/// `#[private(a, b)]` attribute.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    Public,
+    #[default]
+    Private,
+}
+
+/// The kind of Aleo program a package is compiled to, which decides what a
+/// program must contain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    /// A program snarkVM can deploy, which needs at least one function.
+    #[default]
+    SnarkVm,
+    /// A program only imported by others for its types, which may define no
+    /// function at all.
+    Interface,
+    /// A program for the snarkVM forks that assert a boolean with `assert`,
+    /// in one instruction rather than comparing it with `true`. It needs at
+    /// least one function too.
+    Extended,
+}
+
+impl Dialect {
+    /// Whether a boolean is asserted with `assert` rather than `assert.eq`.
+    pub(crate) fn has_boolean_assert(self) -> bool {
+        self == Dialect::Extended
+    }
+}
+
+/// The Aleo instructions generated for a package, along with the metadata
+/// that goes into the emitted file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledProgram {
+    program_name: String,
+    aleo_program: String,
+    /// The program before it was written as text.
+    ir: AleoProgram,
+    description: Option<String>,
+    abi: Vec<FunctionAbi>,
+    opcode_overrides: BTreeMap<String, String>,
+    test_vectors: Vec<TestVector>,
+    skipped_tests: Vec<SkippedTest>,
+    warnings: Vec<String>,
+    profile: Profile,
+}
+
+impl CompiledProgram {
+    pub fn program_name(&self) -> &str {
+        &self.program_name
+    }
+
+    /// The text of the Aleo program, without the description comments that
+    /// are added to the written file.
+    pub fn aleo_program(&self) -> &str {
+        &self.aleo_program
+    }
+
+    /// The name of the file the program is written to.
+    pub fn file_name(&self) -> String {
+        format!("{}.aleo", self.program_name)
+    }
+
+    /// Empty unless [`CompileOptions::emit_test_vectors`] was set.
+    pub fn test_vectors(&self) -> &[TestVector] {
+        &self.test_vectors
+    }
+
+    /// The `#[test]` functions that could not be turned into test vectors.
+    pub fn skipped_tests(&self) -> &[SkippedTest] {
+        &self.skipped_tests
+    }
+
+    /// Problems that did not stop the program from compiling.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+}
+
+/// The Noir signature of a compiled function, kept for tooling that needs
+/// the annotations Aleo has no equivalent for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FunctionAbi {
+    name: String,
+    /// The name and effective visibility of each parameter.
+    parameters: Vec<(String, AbiFEType)>,
+    return_type: String,
+    return_visibility: AbiFEType,
+    return_distinctness: Distinctness,
+}
+
+impl FunctionAbi {
+    fn new(function: &FunctionDefinition) -> Self {
+        FunctionAbi {
+            name: function.name.name.clone(),
+            parameters: function
+                .parameters
+                .iter()
+                .map(|(pattern, _, visibility)| {
+                    let name = parameter_name(pattern).unwrap_or("_");
+                    (name.to_owned(), *visibility)
+                })
+                .collect(),
+            return_type: function.return_type.to_string(),
+            return_visibility: function.return_visibility,
+            return_distinctness: function.return_distinctness,
+        }
+    }
+
+    /// The return annotation as written in the source, e.g. `distinct pub [u32; 2]`.
+    fn return_annotation(&self) -> String {
+        let mut annotation = String::new();
+        if self.return_distinctness == Distinctness::Distinct {
+            annotation.push_str("distinct ");
+        }
+        if self.return_visibility == AbiFEType::Public {
+            annotation.push_str("pub ");
+        }
+        annotation.push_str(&self.return_type);
+        annotation
+    }
+}
+
+fn abi_to_json(abi: &[FunctionAbi]) -> String {
+    let functions: Vec<String> = abi
+        .iter()
+        .map(|function| {
+            let parameters: Vec<String> = function
+                .parameters
+                .iter()
+                .map(|(name, visibility)| {
+                    format!(
+                        "{{\"name\": \"{name}\", \"visibility\": \"{}\"}}",
+                        to_aleo_visibility(visibility)
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"name\": \"{}\", \"parameters\": [{}], \"return\": \"{}\", \"distinct\": {}}}",
+                function.name,
+                parameters.join(", "),
+                function.return_annotation(),
+                function.return_distinctness == Distinctness::Distinct
+            )
+        })
+        .collect();
+    format!("{{\"functions\": [{}]}}\n", functions.join(", "))
+}
+
+/// Where a compiled program is written.
+#[derive(Debug, Clone)]
+pub struct OutputConfig {
+    /// Defaults to `build/aleo`, relative to the current directory.
+    pub output_dir: PathBuf,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        OutputConfig {
+            output_dir: PathBuf::from(ALEO_BUILD_DIR),
+        }
+    }
+}
+
+/// Compiles the Noir package in `program_dir`, the directory of its
+/// `Nargo.toml`, without writing anything.
+///
+/// ```
+/// use std::path::Path;
+///
+/// use noir_to_aleo_instructions::{compile_program, CompileOptions};
+///
+/// let program = compile_program(Path::new("tests/add"), &CompileOptions::default()).unwrap();
+/// assert_eq!(program.file_name(), "add.aleo");
+/// assert!(program.aleo_program().starts_with("program add.aleo;\n"));
+/// ```
+pub fn compile_program(
+    program_dir: &Path,
+    options: &CompileOptions,
+) -> Result<CompiledProgram, CompileError> {
+    compile_to_aleo_instructions(program_dir, options, None)
+}
+
+/// Compiles `source`, the contents of a package's root file, into the Aleo
+/// program `program_name`. It is compiled exactly as a package with no
+/// settings in its manifest, and errors point into `main.nr`.
+///
+/// ```
+/// use noir_to_aleo_instructions::{compile_source, CompileOptions};
+///
+/// let source = "fn main(x : u32, y : u32) -> u32 { x + y }";
+/// let program = compile_source(source, "add", &CompileOptions::default()).unwrap();
+/// assert!(program.aleo_program().starts_with("program add.aleo;\n"));
+/// ```
+pub fn compile_source(
+    source: &str,
+    program_name: &str,
+    options: &CompileOptions,
+) -> Result<CompiledProgram, CompileError> {
+    let mut profile = Profile::default();
+    let package = profile.time("resolve", || resolve_source(source, program_name))?;
+    let mut compiled_program = profile.time("compile", || compile(&package, options))?;
+    profile.append(&mut compiled_program.profile);
+    compiled_program.profile = profile;
+    Ok(compiled_program)
+}
+
+/// Compiles the package in `program_dir`, writing the result as configured
+/// by `output_config`, or only in memory without one.
+pub fn compile_to_aleo_instructions(
+    program_dir: &Path,
+    options: &CompileOptions,
+    output_config: Option<&OutputConfig>,
+) -> Result<CompiledProgram, CompileError> {
+    let mut profile = Profile::default();
+    let package = profile.time("resolve", || resolve(program_dir))?;
+    let mut compiled_program = profile.time("compile", || compile(&package, options))?;
+    if let Some(output_config) = output_config {
+        profile.time("write", || write(&compiled_program, output_config))?;
+    }
+    profile.append(&mut compiled_program.profile);
+    compiled_program.profile = profile;
+    Ok(compiled_program)
+}
+
+/// Reads the manifest and root file of the package in `program_dir`, the
+/// first stage of [`compile_to_aleo_instructions`].
+///
+/// ```
+/// use std::path::Path;
+///
+/// use noir_to_aleo_instructions::{compile, resolve, CompileOptions};
+///
+/// let package = resolve(Path::new("tests/add")).unwrap();
+/// assert_eq!(package.name(), "add");
+/// let program = compile(&package, &CompileOptions::default()).unwrap();
+/// assert_eq!(program.file_name(), "add.aleo");
+/// ```
+pub fn resolve(program_dir: &Path) -> Result<ResolvedPackage, CompileError> {
+    let config = not_nargo::toml::parse(&not_nargo::find_package_manifest(program_dir)?)?;
+    let (noir_ast, source) = not_nargo::into_parsed_program(program_dir)?;
+    let intrinsics = match not_nargo::toml::parse_intrinsics(program_dir)? {
+        Some(file) => IntrinsicTable::new(file)?,
+        None => IntrinsicTable::default(),
+    };
+    Ok(ResolvedPackage {
+        noir_ast,
+        name: not_nargo::package_name(program_dir, &config),
+        root_file: not_nargo::find_package_root_file(program_dir),
+        source,
+        description: config.package.description,
+        opcode_overrides: config.aleo.opcode_overrides,
+        default_visibility: config.aleo.default_visibility,
+        dialect: config.aleo.dialect,
+        max_line_width: config.aleo.max_line_width,
+        bounded_vec: config.aleo.bounded_vec,
+        intrinsics,
+    })
+}
+
+/// A package made only of `source`, as its root file.
+fn resolve_source(source: &str, name: &str) -> Result<ResolvedPackage, CompileError> {
+    let root_file = PathBuf::from(not_nargo::BINARY_ROOT_FILE);
+    Ok(ResolvedPackage {
+        noir_ast: not_nargo::parse_root_file(&root_file, source)?,
+        name: name.to_owned(),
+        root_file,
+        source: source.to_owned(),
+        description: None,
+        opcode_overrides: BTreeMap::new(),
+        default_visibility: None,
+        dialect: None,
+        max_line_width: None,
+        bounded_vec: None,
+        intrinsics: IntrinsicTable::default(),
+    })
+}
+
+/// Compiles a resolved package in memory, the second stage of
+/// [`compile_to_aleo_instructions`]. Errors point into its root file.
+pub fn compile(
+    package: &ResolvedPackage,
+    options: &CompileOptions,
+) -> Result<CompiledProgram, CompileError> {
+    let program_name = options
+        .program_name
+        .clone()
+        .unwrap_or_else(|| package.name.clone());
+    let target = Target {
+        opcodes: OpcodeTable::new(&package.opcode_overrides)?,
+        intrinsics: package.intrinsics.clone(),
+        max_line_width: options.max_line_width.or(package.max_line_width),
+        bounded_vec_capacity: options.bounded_vec.or(package.bounded_vec),
+        dialect: options.dialect.or(package.dialect).unwrap_or_default(),
+    };
+    let default_visibility = options
+        .default_visibility
+        .or(package.default_visibility)
+        .unwrap_or_default();
+    let noir_ast = resolve_visibilities(&package.noir_ast, default_visibility)
+        .map_err(|error| error.locate(&package.root_file, &package.source))?;
+    let mut profile = Profile::default();
+    let mut diagnostics = Vec::new();
+    let mut aleo_program = build_aleo_program(
+        &noir_ast,
+        &program_name,
+        &target,
+        &mut profile,
+        options.collect_diagnostics.then_some(&mut diagnostics),
+    )
+    .map_err(|error| error.locate(&package.root_file, &package.source))?;
+    if !diagnostics.is_empty() {
+        return Err(CompileError::Unsupported(
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| Diagnostic {
+                    error: diagnostic.error.locate(&package.root_file, &package.source),
+                    ..diagnostic
+                })
+                .collect(),
+        ));
+    }
+    let mut warnings = Vec::new();
+    if aleo_program.signatures.is_empty() {
+        match target.dialect {
+            Dialect::SnarkVm | Dialect::Extended => return Err(CompileError::NoFunctions),
+            Dialect::Interface => {
+                warnings.push("the program defines no functions, only its types".to_owned())
+            }
+        }
+    }
+    profile
+        .time("fold constants", || {
+            optimize::fold_constants(&mut aleo_program)
+        })
+        .map_err(|error| error.locate(&package.root_file, &package.source))?;
+    if options.optimize {
+        profile.time("eliminate common subexpressions", || {
+            optimize::eliminate_common_subexpressions(&mut aleo_program)
+        });
+        profile.time("collapse repeated multiplications", || {
+            optimize::collapse_repeated_multiplications(&mut aleo_program)
+        });
+    }
+    profile.time("eliminate dead code", || {
+        optimize::eliminate_dead_code(&mut aleo_program)
+    });
+    let (test_vectors, skipped_tests) = if options.emit_test_vectors {
+        test_vectors::extract(&noir_ast)
+    } else {
+        Default::default()
+    };
+    Ok(CompiledProgram {
+        program_name,
+        aleo_program: aleo_program.to_string(),
+        ir: aleo_program,
+        description: package.description.clone(),
+        abi: program_functions(&noir_ast).map(FunctionAbi::new).collect(),
+        opcode_overrides: package.opcode_overrides.clone(),
+        test_vectors,
+        skipped_tests,
+        warnings,
+        profile,
+    })
+}
+
+/// Writes a compiled program to the configured file, prefixed by the package
+/// description as comments. Its ABI and the build settings that changed the
+/// emitted code go to `abi.json` and `manifest.json` next to it, and its test
+/// vectors to the `tests` directory. This is the only place output files are
+/// written, so it is also where their bytes are normalized. This is the last
+/// stage of [`compile_to_aleo_instructions`], and returns the path of the
+/// program.
+pub fn write(
+    compiled_program: &CompiledProgram,
+    output_config: &OutputConfig,
+) -> Result<PathBuf, CompileError> {
+    write_files(compiled_program, output_config).map_err(|error| CompileError::Io {
+        path: output_config.output_dir.join(compiled_program.file_name()),
+        message: error.to_string(),
+    })
+}
+
+fn write_files(
+    compiled_program: &CompiledProgram,
+    output_config: &OutputConfig,
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(&output_config.output_dir)?;
+    let path = output_config.output_dir.join(compiled_program.file_name());
+    fs::write(
+        &path,
+        normalize_aleo_output(
+            &compiled_program.aleo_program,
+            compiled_program.description.as_deref(),
+        ),
+    )?;
+    fs::write(
+        output_config.output_dir.join("abi.json"),
+        abi_to_json(&compiled_program.abi),
+    )?;
+    fs::write(
+        output_config.output_dir.join("manifest.json"),
+        manifest_to_json(&compiled_program.opcode_overrides),
+    )?;
+    if !compiled_program.test_vectors.is_empty() {
+        let tests_dir = output_config.output_dir.join("tests");
+        fs::create_dir_all(&tests_dir)?;
+        for test_vector in &compiled_program.test_vectors {
+            fs::write(
+                tests_dir.join(format!("{}.json", test_vector.name)),
+                test_vector.to_json(),
+            )?;
+        }
+    }
+    Ok(path)
+}
+
+fn manifest_to_json(opcode_overrides: &BTreeMap<String, String>) -> String {
+    let overrides: Vec<String> = opcode_overrides
+        .iter()
+        .map(|(opcode, replacement)| format!("\"{opcode}\": \"{replacement}\""))
+        .collect();
+    format!("{{\"opcode_overrides\": {{{}}}}}\n", overrides.join(", "))
+}
+
+/// Deployment hashes depend on the exact bytes of a program, so the emitted
+/// file always uses `\n` line endings and ends with exactly one newline,
+/// whatever the host platform or the metadata injected into it.
+fn normalize_aleo_output(aleo_program: &str, description: Option<&str>) -> String {
+    let mut output = String::new();
+    if let Some(description) = description {
+        for line in description.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                output.push_str("//");
+            } else {
+                output.push_str(&format!("// {line}"));
+            }
+            push_new_line(&mut output);
+        }
+    }
+    output.push_str(&aleo_program.replace('\r', ""));
+    let trimmed_length = output.trim_end_matches('\n').len();
+    output.truncate(trimmed_length);
+    push_new_line(&mut output);
+    output
+}
+
+/// Lowers a parsed package to an Aleo program. Given `diagnostics`, errors in
+/// function bodies are recorded there and the build goes on with the rest of
+/// the program instead of stopping at the first one.
+fn build_aleo_program(
+    noir_ast: &ParsedModule,
+    program_name: &str,
+    target: &Target,
+    profile: &mut Profile,
+    mut diagnostics: Option<&mut Vec<Diagnostic>>,
+) -> Result<AleoProgram, CompileError> {
+    validate_identifiers(noir_ast, program_name)?;
+    let mut aleo_program = AleoProgram::new(program_name);
+    let struct_registry = collect_structs(noir_ast);
+    check_struct_layouts(&struct_registry)?;
+    // Aleo requires types to be defined before the functions using them.
+    for noir_struct in struct_registry.values() {
+        for (_, typ) in &noir_struct.fields {
+            check_declared_types(typ, &struct_registry)?;
+        }
+        let aleo_struct = to_aleo_struct_definition(noir_struct)
+            .map_err(|error| error.with_span(noir_struct.span))?;
+        aleo_program.push_item(aleo_struct);
+        if is_record(noir_struct) {
+            aleo_program.records.push(noir_struct.name.name.clone());
+        }
+    }
+    let mappings = collect_mappings(noir_ast)?;
+    for (name, mapping) in &mappings {
+        aleo_program.push_item(to_aleo_mapping_definition(name, mapping));
+    }
+    let called_functions = called_functions(noir_ast);
+    for function in program_functions(noir_ast) {
+        let kind = callable_kind(function, &called_functions);
+        let mut function_errors = Vec::new();
+        let aleo_function = profile.time(format!("function {}", function.name), || {
+            compile_function(
+                function,
+                kind,
+                noir_ast,
+                &struct_registry,
+                &mappings,
+                target,
+                diagnostics.is_some().then_some(&mut function_errors),
+            )
+            .map_err(|error| error.with_span(function.span))
+        });
+        match aleo_function {
+            Ok(callable) => {
+                // Like the other structs, those of tuples come before every
+                // function.
+                for (name, element_types) in callable.tuple_structs {
+                    let is_declared = aleo_program.items.iter().any(
+                        |item| matches!(item, Item::Struct(composite) if composite.name == name),
+                    );
+                    if !is_declared {
+                        let position = aleo_program
+                            .items
+                            .iter()
+                            .position(|item| matches!(item, Item::Function(_)))
+                            .unwrap_or(aleo_program.items.len());
+                        aleo_program.items.insert(
+                            position,
+                            Item::Struct(Composite {
+                                name,
+                                members: element_types
+                                    .into_iter()
+                                    .enumerate()
+                                    .map(|(index, typ)| (tuple_member(index), typ))
+                                    .collect(),
+                            }),
+                        );
+                    }
+                }
+                aleo_program.push_item(Item::Function(callable.function));
+                aleo_program
+                    .signatures
+                    .insert(function.name.name.clone(), callable.signature);
+                aleo_program.call_sites.extend(callable.call_sites);
+            }
+            Err(error) if diagnostics.is_some() => function_errors.push(error),
+            Err(error) => return Err(error),
+        }
+        if let Some(diagnostics) = diagnostics.as_deref_mut() {
+            diagnostics.extend(function_errors.into_iter().map(|error| Diagnostic {
+                function: function.name.name.clone(),
+                error,
+            }));
+        }
+    }
+    // Calls are only consistent once every callee compiled.
+    if diagnostics.is_none_or(|diagnostics| diagnostics.is_empty()) {
+        validator::validate(&aleo_program, target.dialect)?;
+    }
+    Ok(aleo_program)
+}
+
+/// Gives the parameters declared without `pub` their effective visibility:
+/// `default_visibility`, unless the function keeps them private with a
+/// `#[private(..)]` attribute.
+fn resolve_visibilities(
+    noir_ast: &ParsedModule,
+    default_visibility: Visibility,
+) -> Result<ParsedModule, CompileError> {
+    let mut noir_ast = noir_ast.clone();
+    for function in &mut noir_ast.functions {
+        let mut kept_private = Vec::new();
+        for attribute in &function.attributes {
+            if attribute.name != "private" {
+                continue;
+            }
+            for name in &attribute.arguments {
+                let parameter = function
+                    .parameters
+                    .iter()
+                    .find(|(pattern, _, _)| parameter_name(pattern) == Some(name.as_str()));
+                let reason = match parameter {
+                    None => "it is not one of its parameters",
+                    Some((_, _, AbiFEType::Public)) => "it is declared `pub`",
+                    Some(_) => {
+                        kept_private.push(name.clone());
+                        continue;
+                    }
+                };
+                return Err(CompileError::InvalidVisibilityOverride {
+                    function: function.name.name.clone(),
+                    parameter: name.clone(),
+                    reason,
+                }
+                .with_span(attribute.span));
+            }
+        }
+        if default_visibility == Visibility::Public {
+            for (pattern, _, visibility) in &mut function.parameters {
+                let is_kept_private = parameter_name(pattern)
+                    .is_some_and(|name| kept_private.iter().any(|kept| kept == name));
+                if !is_kept_private {
+                    *visibility = AbiFEType::Public;
+                }
+            }
+        }
+    }
+    Ok(noir_ast)
+}
+
+/// The name a parameter is bound to, if it binds a single one.
+fn parameter_name(pattern: &Pattern) -> Option<&str> {
+    match pattern {
+        Pattern::Identifier(ident) => Some(&ident.name),
+        Pattern::Mutable(pattern, _) => parameter_name(pattern),
+        Pattern::Tuple(_, _) | Pattern::Struct(_, _, _) => None,
+    }
+}
+
+/// The functions that make up the Aleo program, leaving out Noir tests and
+/// finalize blocks.
+fn program_functions(noir_ast: &ParsedModule) -> impl Iterator<Item = &FunctionDefinition> {
+    noir_ast
+        .functions
+        .iter()
+        .filter(|function| !is_test(function) && !is_finalize(function))
+}
+
+fn is_test(function: &FunctionDefinition) -> bool {
+    has_attribute(function, "test")
+}
+
+/// Whether `function` is the on-chain part of the functions calling it,
+/// emitted as their `finalize` blocks rather than on its own.
+fn is_finalize(function: &FunctionDefinition) -> bool {
+    has_attribute(function, "finalize")
+}
+
+fn has_attribute(function: &FunctionDefinition, name: &str) -> bool {
+    function
+        .attributes
+        .iter()
+        .any(|attribute| attribute.name == name)
+}
+
+/// The callable kind given by a `#[transition]`, `#[function]` or `#[closure]`
+/// attribute. Without one, functions called from the program are helper
+/// functions and the others are transitions.
+fn callable_kind(
+    function: &FunctionDefinition,
+    called_functions: &HashSet<String>,
+) -> CallableKind {
+    if has_attribute(function, "transition") {
+        CallableKind::Transition
+    } else if has_attribute(function, "closure") {
+        CallableKind::Closure
+    } else if has_attribute(function, "function") || called_functions.contains(&function.name.name)
+    {
+        CallableKind::Function
+    } else {
+        CallableKind::Transition
+    }
+}
+
+/// The names of the functions called by the functions of the program.
+fn called_functions(noir_ast: &ParsedModule) -> HashSet<String> {
+    let mut called_functions = HashSet::new();
+    for function in program_functions(noir_ast) {
+        for statement in &function.body.0 {
+            collect_statement_calls(statement, &mut called_functions);
+        }
+    }
+    called_functions
+}
+
+fn collect_statement_calls(statement: &Statement, called_functions: &mut HashSet<String>) {
+    match statement {
+        Statement::Let(let_statement) => collect_calls(&let_statement.expression, called_functions),
+        Statement::Constrain(ConstrainStatement(expression))
+        | Statement::Expression(expression)
+        | Statement::Semi(expression) => collect_calls(expression, called_functions),
+        Statement::Assign(assign_statement) => {
+            collect_calls(&assign_statement.expression, called_functions)
+        }
+        Statement::Error => {}
+    }
+}
+
+fn collect_calls(expression: &Expression, called_functions: &mut HashSet<String>) {
+    match &expression.kind {
+        ExpressionKind::Call(call) => {
+            // Only a call through a plain name calls a function of the
+            // program, as in `compile_call`.
+            if let ExpressionKind::Path(path) = &call.func.kind {
+                if let Some(ident) = path.as_ident() {
+                    called_functions.insert(ident.name.clone());
+                }
+            }
+            for argument in &call.arguments {
+                collect_calls(argument, called_functions);
+            }
+        }
+        ExpressionKind::Literal(Literal::Array(ArrayLiteral::Standard(elements)))
+        | ExpressionKind::Tuple(elements) => {
+            for element in elements {
+                collect_calls(element, called_functions);
+            }
+        }
+        ExpressionKind::Literal(Literal::Array(ArrayLiteral::Repeated {
+            repeated_element,
+            length,
+        })) => {
+            collect_calls(repeated_element, called_functions);
+            collect_calls(length, called_functions);
+        }
+        ExpressionKind::Block(block) => {
+            for statement in &block.0 {
+                collect_statement_calls(statement, called_functions);
+            }
+        }
+        ExpressionKind::Prefix(prefix) => collect_calls(&prefix.rhs, called_functions),
+        ExpressionKind::Index(index) => {
+            collect_calls(&index.collection, called_functions);
+            collect_calls(&index.index, called_functions);
+        }
+        ExpressionKind::MethodCall(method_call) => {
+            collect_calls(&method_call.object, called_functions);
+            for argument in &method_call.arguments {
+                collect_calls(argument, called_functions);
+            }
+        }
+        ExpressionKind::Constructor(constructor) => {
+            for (_, field) in &constructor.fields {
+                collect_calls(field, called_functions);
+            }
+        }
+        ExpressionKind::MemberAccess(member_access) => {
+            collect_calls(&member_access.lhs, called_functions)
+        }
+        ExpressionKind::Cast(cast) => collect_calls(&cast.lhs, called_functions),
+        ExpressionKind::Infix(infix) => {
+            collect_calls(&infix.lhs, called_functions);
+            collect_calls(&infix.rhs, called_functions);
+        }
+        ExpressionKind::If(if_expression) => {
+            collect_calls(&if_expression.condition, called_functions);
+            collect_calls(&if_expression.consequence, called_functions);
+            if let Some(alternative) = &if_expression.alternative {
+                collect_calls(alternative, called_functions);
+            }
+        }
+        ExpressionKind::For(for_expression) => {
+            collect_calls(&for_expression.start_range, called_functions);
+            collect_calls(&for_expression.end_range, called_functions);
+            collect_calls(&for_expression.block, called_functions);
+        }
+        ExpressionKind::Literal(_) | ExpressionKind::Path(_) | ExpressionKind::Error => {}
+    }
+}
+
+/// Rejects the names that would be emitted into the program but are not
+/// valid Aleo identifiers.
+fn validate_identifiers(noir_ast: &ParsedModule, program_name: &str) -> Result<(), CompileError> {
+    identifier::validate("program", program_name)?;
+    for noir_struct in &noir_ast.types {
+        identifier::validate("struct", &noir_struct.name.name)?;
+        for (field, _) in &noir_struct.fields {
+            identifier::validate("field", &field.name)?;
+        }
+    }
+    for global in &noir_ast.globals {
+        if global
+            .attributes
+            .iter()
+            .any(|attribute| attribute.name == "mapping")
+        {
+            identifier::validate("mapping", &global.name.name)?;
+        }
+    }
+    for function in program_functions(noir_ast) {
+        identifier::validate("function", &function.name.name)?;
+    }
+    Ok(())
+}
+
+fn collect_structs(noir_ast: &ParsedModule) -> StructRegistry<'_> {
+    noir_ast
+        .types
+        .iter()
+        .map(|noir_struct| (noir_struct.name.name.clone(), noir_struct))
+        .collect()
+}
+
+/// Rejects the structs and records Aleo cannot lay out: those containing
+/// themselves, through any chain of members, and those containing records.
+fn check_struct_layouts(struct_registry: &StructRegistry) -> Result<(), CompileError> {
+    for noir_struct in struct_registry.values() {
+        for (field, typ) in &noir_struct.fields {
+            let contains_record = member_structs(typ, struct_registry).any(is_record);
+            if contains_record {
+                return Err(CompileError::InvalidStructMember {
+                    struct_name: noir_struct.name.name.clone(),
+                    field: field.name.clone(),
+                    reason: if is_record(noir_struct) {
+                        "records cannot contain other records"
+                    } else {
+                        "structs cannot contain records"
+                    },
+                }
+                .with_span(noir_struct.span));
+            }
+        }
+    }
+    let mut checked = HashSet::new();
+    for noir_struct in struct_registry.values() {
+        let mut chain = Vec::new();
+        find_struct_cycle(noir_struct, struct_registry, &mut checked, &mut chain)
+            .map_err(|error| error.with_span(noir_struct.span))?;
+    }
+    Ok(())
+}
+
+/// Walks the structs contained in `noir_struct` depth first, with `chain`
+/// holding the path from the struct the walk started from. Every struct is
+/// walked at most once, since `checked` records those already known to be
+/// free of cycles.
+fn find_struct_cycle<'a>(
+    noir_struct: &'a NoirStruct,
+    struct_registry: &StructRegistry<'a>,
+    checked: &mut HashSet<&'a str>,
+    chain: &mut Vec<&'a str>,
+) -> Result<(), CompileError> {
+    let name = noir_struct.name.name.as_str();
+    if checked.contains(name) {
+        return Ok(());
+    }
+    if let Some(start) = chain.iter().position(|member| *member == name) {
+        let mut cycle: Vec<String> = chain[start..].iter().map(|name| name.to_string()).collect();
+        cycle.push(name.to_owned());
+        return Err(CompileError::RecursiveStruct(cycle));
+    }
+    chain.push(name);
+    for (_, typ) in &noir_struct.fields {
+        for member in member_structs(typ, struct_registry) {
+            find_struct_cycle(member, struct_registry, checked, chain)?;
+        }
+    }
+    chain.pop();
+    checked.insert(name);
+    Ok(())
+}
+
+/// The structs of the program a value of `typ` directly contains: the type
+/// itself or, for arrays, that of their elements.
+fn member_structs<'a, 'r>(
+    typ: &UnresolvedType,
+    struct_registry: &'r StructRegistry<'a>,
+) -> impl Iterator<Item = &'a NoirStruct> + 'r {
+    let mut typ = typ;
+    while let UnresolvedType::Array(_, element_type) = typ {
+        typ = element_type;
+    }
+    let member = match typ {
+        UnresolvedType::Named(path) => struct_registry.get(&path.last_segment().name).copied(),
+        _ => None,
+    };
+    member.into_iter()
+}
+
+fn is_record(noir_struct: &NoirStruct) -> bool {
+    noir_struct
+        .attributes
+        .iter()
+        .any(|attribute| attribute.name == "record")
+}
+
+fn to_aleo_struct_definition(noir_struct: &NoirStruct) -> Result<Item, CompileError> {
+    if is_record(noir_struct) {
+        return to_aleo_record_definition(noir_struct);
+    }
+    let mut members = Vec::new();
+    for (field, typ) in &noir_struct.fields {
+        members.push((field.name.clone(), to_aleo_type(typ)?));
+    }
+    Ok(Item::Struct(Composite {
+        name: noir_struct.name.name.clone(),
+        members,
+    }))
+}
+
+fn collect_mappings(noir_ast: &ParsedModule) -> Result<MappingRegistry, CompileError> {
+    let mut mappings = MappingRegistry::new();
+    for global in &noir_ast.globals {
+        let Some(attribute) = global
+            .attributes
+            .iter()
+            .find(|attribute| attribute.name == "mapping")
+        else {
+            continue;
+        };
+        let invalid_mapping = |reason| CompileError::InvalidMapping {
+            name: global.name.name.clone(),
+            reason,
+        };
+        let [key_type] = attribute.arguments.as_slice() else {
+            return Err(invalid_mapping(
+                "the key type must be given as `#[mapping(KeyType)]`",
+            ));
+        };
+        let Some(value_type) = &global.r#type else {
+            return Err(invalid_mapping("the value type must be annotated"));
+        };
+        let value_type = to_aleo_type(value_type)?;
+        let default = match &global.expression.kind {
+            ExpressionKind::Literal(Literal::Integer(value, _)) => Operand::Integer {
+                value: *value,
+                typ: value_type.clone(),
+            },
+            ExpressionKind::Literal(Literal::Bool(value)) => Operand::Boolean(*value),
+            _ => return Err(invalid_mapping("the default value must be a literal")),
+        };
+        let key_type = to_aleo_type(&type_from_name(key_type, attribute.span))?;
+        mappings.insert(
+            global.name.name.clone(),
+            AleoMapping {
+                key_type,
+                value_type,
+                default,
+            },
+        );
+    }
+    Ok(mappings)
+}
+
+/// Parses a type written as a single identifier, such as an attribute argument.
+fn type_from_name(name: &str, span: Span) -> UnresolvedType {
+    let integer_bits = |prefix| name.strip_prefix(prefix).and_then(|bits| bits.parse().ok());
+    if let Some(bits) = integer_bits('u') {
+        return UnresolvedType::Integer(Signedness::Unsigned, bits);
+    }
+    if let Some(bits) = integer_bits('i') {
+        return UnresolvedType::Integer(Signedness::Signed, bits);
+    }
+    match name {
+        "Field" => UnresolvedType::FieldElement,
+        "bool" => UnresolvedType::Bool,
+        _ => UnresolvedType::Named(noir_parser::Path {
+            segments: vec![Ident {
+                name: name.to_owned(),
+                span,
+            }],
+        }),
+    }
+}
+
+fn to_aleo_mapping_definition(name: &str, mapping: &AleoMapping) -> Item {
+    Item::Mapping(Mapping {
+        name: name.to_owned(),
+        key_type: mapping.key_type.clone(),
+        value_type: mapping.value_type.clone(),
+    })
+}
+
+/// Lowers a `#[record]` struct, adding the ownership fields it does not
+/// declare. Noir struct fields have no visibility, so every field is private.
+fn to_aleo_record_definition(noir_struct: &NoirStruct) -> Result<Item, CompileError> {
+    let mut fields = Vec::new();
+    for (field, typ) in &noir_struct.fields {
+        fields.push((field.name.as_str(), to_aleo_type(typ)?));
+    }
+    let mut members = Vec::new();
+    for (field, expected_type) in RECORD_OWNERSHIP_FIELDS {
+        if let Some((_, aleo_type)) = fields.iter().find(|(name, _)| name == field) {
+            if aleo_type != expected_type {
+                return Err(CompileError::InvalidRecordField {
+                    record: noir_struct.name.name.clone(),
+                    field: field.to_string(),
+                    expected: expected_type,
+                    found: aleo_type.clone(),
+                });
+            }
+        }
+        members.push((field.to_string(), expected_type.to_string()));
+    }
+    for (field, aleo_type) in &fields {
+        if RECORD_OWNERSHIP_FIELDS
+            .iter()
+            .all(|(ownership_field, _)| ownership_field != field)
+        {
+            members.push((field.to_string(), aleo_type.clone()));
+        }
+    }
+    Ok(Item::Record(Composite {
+        name: noir_struct.name.name.clone(),
+        members,
+    }))
+}
+
+/// A compiled callable, with what the validator needs to check the calls to
+/// and from it.
+struct CompiledCallable {
+    function: AleoFunction,
+    signature: Signature,
+    call_sites: Vec<CallSite>,
+    tuple_structs: IndexMap<String, Vec<String>>,
+}
+
+fn compile_function(
+    function: &FunctionDefinition,
+    kind: CallableKind,
+    noir_ast: &ParsedModule,
+    struct_registry: &StructRegistry,
+    mappings: &MappingRegistry,
+    target: &Target,
+    mut diagnostics: Option<&mut Vec<CompileError>>,
+) -> Result<CompiledCallable, CompileError> {
+    let mut context = FunctionContext::new(noir_ast, struct_registry, mappings, target, kind);
+    context.function_name = function.name.name.clone();
+    context.function.name = function.name.name.clone();
+
+    // In collect mode, whatever a failing parameter or statement emitted is
+    // rolled back and it is given fixed placeholder registers instead, so
+    // that the numbering of everything after it does not depend on how far
+    // it got before failing.
+    for (pattern, typ, visibility) in &function.parameters {
+        let checkpoint = context.checkpoint();
+        match compile_parameter(pattern, typ, visibility, &mut context) {
+            Ok(()) => {}
+            Err(error) => {
+                let Some(diagnostics) = diagnostics.as_deref_mut() else {
+                    return Err(error);
+                };
+                diagnostics.push(error.with_span(pattern.span()));
+                context.rollback(checkpoint);
+                bind_parameter_placeholders(pattern, typ, &mut context);
+            }
+        }
+    }
+
+    check_declared_types(&function.return_type, struct_registry)?;
+
+    let signature = Signature {
+        kind,
+        inputs: (0..context.register_count)
+            .map(|index| context.register_types.get(&Register(index).into()).cloned())
+            .collect(),
+    };
+
+    collect_let_bindings(&function.body, &mut context.let_bindings);
+    let mut tail_operands = None;
+    for (index, statement) in function.body.0.iter().enumerate() {
+        let is_tail = index == function.body.0.len() - 1;
+        let checkpoint = context.checkpoint();
+        let registers =
+            match compile_statement(statement, is_tail, &function.return_type, &mut context) {
+                Ok(registers) => registers,
+                Err(error) => {
+                    let error = match statement.span() {
+                        Some(span) => error.with_span(span),
+                        None => error,
+                    };
+                    let Some(diagnostics) = diagnostics.as_deref_mut() else {
+                        return Err(error);
+                    };
+                    diagnostics.push(error);
+                    context.rollback(checkpoint);
+                    bind_placeholders(statement, is_tail, &function.return_type, &mut context)
+                }
+            };
+        if registers.is_some() {
+            tail_operands = registers;
+        }
+    }
+
+    // Aleo has no tuple type, so each component of a tuple return value is
+    // its own output.
+    let outputs = match (&function.return_type, tail_operands) {
+        (UnresolvedType::Tuple(element_types), Some(registers)) => {
+            let aleo_types = element_types
+                .iter()
+                .map(to_aleo_type)
+                .collect::<Result<Vec<_>, _>>()?;
+            registers.into_iter().zip(aleo_types).collect()
+        }
+        // Aleo functions without a result simply have no `output` line.
+        (UnresolvedType::Unit, _) => Vec::new(),
+        (return_type, tail_operands) => {
+            let aleo_type = to_aleo_type(return_type)?;
+            if function.body.0.is_empty() {
+                return Err(CompileError::MissingReturnValue {
+                    function: function.name.name.clone(),
+                    return_type: aleo_type,
+                });
+            }
+            let Some([output_register]) = tail_operands.as_deref() else {
+                return Err(CompileError::TypeMismatch {
+                    expected: aleo_type,
+                    found: "()".to_owned(),
+                });
+            };
+            match aleo_array_layout(&aleo_type) {
+                // A public array is a list of public values in Noir's ABI,
+                // so each element gets its own output.
+                Some((element_type, length)) if function.return_visibility == AbiFEType::Public => {
+                    (0..length)
+                        .map(|index| {
+                            (
+                                Operand::Index {
+                                    base: Box::new(output_register.clone()),
+                                    index: u128::from(index),
+                                },
+                                element_type.to_owned(),
+                            )
+                        })
+                        .collect()
+                }
+                _ => vec![(output_register.clone(), aleo_type)],
+            }
+        }
+    };
+    for (operand, typ) in outputs {
+        if let Some(found) = context.register_types.get(&operand) {
+            if *found != typ {
+                return Err(CompileError::TypeMismatch {
+                    expected: typ,
+                    found: found.clone(),
+                });
+            }
+        }
+        let output = match context.declared_type(&typ) {
+            Some(record_type) => Output {
+                operand,
+                typ: record_type,
+                visibility: None,
+            },
+            None => Output {
+                operand,
+                typ,
+                visibility: context.visibility(&function.return_visibility),
+            },
+        };
+        context.function.outputs.push(output);
+    }
+    if let Some((finalize_function, operands)) = context.finalize.take() {
+        let finalize_block = compile_finalize_block(
+            &function.name,
+            finalize_function,
+            noir_ast,
+            struct_registry,
+            mappings,
+            target,
+            diagnostics,
+        )?;
+        context.function.finalize = Some((operands, Box::new(finalize_block.function)));
+        context.tuple_structs.extend(finalize_block.tuple_structs);
+    }
+    Ok(CompiledCallable {
+        function: context.function,
+        signature,
+        call_sites: context.call_sites,
+        tuple_structs: context.tuple_structs,
+    })
+}
+
+/// Compiles `finalize_function` as the `finalize` block of `caller`.
+fn compile_finalize_block(
+    caller: &Ident,
+    finalize_function: &FunctionDefinition,
+    noir_ast: &ParsedModule,
+    struct_registry: &StructRegistry,
+    mappings: &MappingRegistry,
+    target: &Target,
+    diagnostics: Option<&mut Vec<CompileError>>,
+) -> Result<CompiledCallable, CompileError> {
+    if finalize_function.return_type != UnresolvedType::Unit {
+        return Err(CompileError::InvalidFinalize {
+            function: finalize_function.name.name.clone(),
+            reason: "finalize functions cannot return a value",
+        });
+    }
+    // Finalize blocks run on-chain, where every input is public.
+    let mut block = finalize_function.clone();
+    block.name = caller.clone();
+    for (_, _, visibility) in &mut block.parameters {
+        *visibility = AbiFEType::Public;
+    }
+    compile_function(
+        &block,
+        CallableKind::Finalize,
+        noir_ast,
+        struct_registry,
+        mappings,
+        target,
+        diagnostics,
+    )
+}
+
+/// A name bound by a `let` statement, and where the block the binding is
+/// visible in starts.
+struct LetBinding {
+    name: String,
+    span: Span,
+    scope_start: usize,
+}
+
+/// Collects the names bound by every `let` statement of `block`, including
+/// those of the blocks nested in it, in the order they appear in.
+fn collect_let_bindings(block: &BlockExpression, bindings: &mut Vec<LetBinding>) {
+    fn pattern_bindings(pattern: &Pattern, scope_start: usize, bindings: &mut Vec<LetBinding>) {
+        match pattern {
+            Pattern::Identifier(ident) => bindings.push(LetBinding {
+                name: ident.name.clone(),
+                span: ident.span,
+                scope_start,
+            }),
+            Pattern::Mutable(pattern, _) => pattern_bindings(pattern, scope_start, bindings),
+            Pattern::Tuple(patterns, _) => {
+                for pattern in patterns {
+                    pattern_bindings(pattern, scope_start, bindings);
+                }
+            }
+            Pattern::Struct(_, fields, _) => {
+                for (_, pattern) in fields {
+                    pattern_bindings(pattern, scope_start, bindings);
+                }
+            }
+        }
+    }
+    let Some(scope_start) = block.0.iter().find_map(|statement| statement.span()) else {
+        return;
+    };
+    for statement in &block.0 {
+        match statement {
+            Statement::Let(let_statement) => {
+                collect_expression_let_bindings(&let_statement.expression, bindings);
+                pattern_bindings(&let_statement.pattern, scope_start.start, bindings);
+            }
+            Statement::Constrain(ConstrainStatement(expression))
+            | Statement::Expression(expression)
+            | Statement::Semi(expression) => collect_expression_let_bindings(expression, bindings),
+            Statement::Assign(assign_statement) => {
+                collect_expression_let_bindings(&assign_statement.expression, bindings)
+            }
+            Statement::Error => {}
+        }
+    }
+}
+
+fn collect_expression_let_bindings(expression: &Expression, bindings: &mut Vec<LetBinding>) {
+    match &expression.kind {
+        ExpressionKind::Block(block) => collect_let_bindings(block, bindings),
+        ExpressionKind::Call(call) => {
+            for argument in &call.arguments {
+                collect_expression_let_bindings(argument, bindings);
+            }
+        }
+        ExpressionKind::Literal(Literal::Array(ArrayLiteral::Standard(elements)))
+        | ExpressionKind::Tuple(elements) => {
+            for element in elements {
+                collect_expression_let_bindings(element, bindings);
+            }
+        }
+        ExpressionKind::Literal(Literal::Array(ArrayLiteral::Repeated {
+            repeated_element,
+            length,
+        })) => {
+            collect_expression_let_bindings(repeated_element, bindings);
+            collect_expression_let_bindings(length, bindings);
+        }
+        ExpressionKind::Prefix(prefix) => collect_expression_let_bindings(&prefix.rhs, bindings),
+        ExpressionKind::Index(index) => {
+            collect_expression_let_bindings(&index.collection, bindings);
+            collect_expression_let_bindings(&index.index, bindings);
+        }
+        ExpressionKind::MethodCall(method_call) => {
+            collect_expression_let_bindings(&method_call.object, bindings);
+            for argument in &method_call.arguments {
+                collect_expression_let_bindings(argument, bindings);
+            }
+        }
+        ExpressionKind::Constructor(constructor) => {
+            for (_, field) in &constructor.fields {
+                collect_expression_let_bindings(field, bindings);
+            }
+        }
+        ExpressionKind::MemberAccess(member_access) => {
+            collect_expression_let_bindings(&member_access.lhs, bindings)
+        }
+        ExpressionKind::Cast(cast) => collect_expression_let_bindings(&cast.lhs, bindings),
+        ExpressionKind::Infix(infix) => {
+            collect_expression_let_bindings(&infix.lhs, bindings);
+            collect_expression_let_bindings(&infix.rhs, bindings);
+        }
+        ExpressionKind::If(if_expression) => {
+            collect_expression_let_bindings(&if_expression.condition, bindings);
+            collect_expression_let_bindings(&if_expression.consequence, bindings);
+            if let Some(alternative) = &if_expression.alternative {
+                collect_expression_let_bindings(alternative, bindings);
+            }
+        }
+        ExpressionKind::For(for_expression) => {
+            collect_expression_let_bindings(&for_expression.start_range, bindings);
+            collect_expression_let_bindings(&for_expression.end_range, bindings);
+            collect_expression_let_bindings(&for_expression.block, bindings);
+        }
+        ExpressionKind::Literal(_) | ExpressionKind::Path(_) | ExpressionKind::Error => {}
+    }
+}
+
+/// Reserves one register per input a parameter that failed to compile would
+/// have had, binding its name when it has a single one.
+fn bind_parameter_placeholders(
+    pattern: &Pattern,
+    typ: &UnresolvedType,
+    context: &mut FunctionContext,
+) {
+    let register = context.new_register(None);
+    if let (Pattern::Identifier(ident), false) = (pattern, matches!(typ, UnresolvedType::Tuple(_)))
+    {
+        context.bind(ident.name.clone(), register.into());
+    }
+    for _ in 1..flattened_input_count(typ) {
+        context.new_register(None);
+    }
+}
+
+/// The number of inputs a parameter of type `typ` is lowered to.
+fn flattened_input_count(typ: &UnresolvedType) -> usize {
+    match typ {
+        UnresolvedType::Tuple(element_types) => {
+            element_types.iter().map(flattened_input_count).sum()
+        }
+        _ => 1,
+    }
+}
+
+/// Binds the names a statement that failed to compile would have defined to
+/// fresh registers, so that the statements after it can still be checked.
+/// Returns the operands of a tail expression, like `compile_statement`.
+fn bind_placeholders(
+    statement: &Statement,
+    is_tail: bool,
+    return_type: &UnresolvedType,
+    context: &mut FunctionContext,
+) -> Option<Vec<Operand>> {
+    match statement {
+        Statement::Let(let_statement) => {
+            // The annotated type, if any, keeps uses of the names type-checked.
+            let bindings: Vec<(&Ident, Option<&UnresolvedType>)> =
+                match (&let_statement.pattern, &let_statement.r#type) {
+                    (Pattern::Identifier(ident), typ) => vec![(ident, typ.as_ref())],
+                    (Pattern::Tuple(patterns, _), typ) => patterns
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(index, pattern)| match pattern {
+                            Pattern::Identifier(ident) => {
+                                let element_type = match typ {
+                                    Some(UnresolvedType::Tuple(element_types)) => {
+                                        element_types.get(index)
+                                    }
+                                    _ => None,
+                                };
+                                Some((ident, element_type))
+                            }
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+            for (ident, typ) in bindings {
+                let aleo_type = typ.and_then(|typ| to_aleo_type(typ).ok());
+                let register = context.new_register(aleo_type);
+                context.bind(ident.name.clone(), register.into());
+            }
+            None
+        }
+        Statement::Expression(_) => match return_type {
+            UnresolvedType::Tuple(element_types) if is_tail => Some(
+                element_types
+                    .iter()
+                    .map(|_| context.new_register(None).into())
+                    .collect(),
+            ),
+            _ => {
+                let register = context.new_register(None);
+                is_tail.then(|| vec![register.into()])
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Compiles one statement of a function body, returning the operands holding
+/// the value of a tail expression: one per component for a tuple returning
+/// function, whose tail is not emitted on its own, and one otherwise.
+fn compile_statement(
+    statement: &Statement,
+    is_tail: bool,
+    return_type: &UnresolvedType,
+    context: &mut FunctionContext,
+) -> Result<Option<Vec<Operand>>, CompileError> {
+    match statement {
+        Statement::Let(let_statement) => match &let_statement.pattern {
+            Pattern::Identifier(ident) => {
+                let arity = match (&let_statement.r#type, &let_statement.expression.kind) {
+                    (Some(UnresolvedType::Tuple(element_types)), _) => Some(element_types.len()),
+                    (_, ExpressionKind::Tuple(elements)) => Some(elements.len()),
+                    _ => None,
+                };
+                match arity {
+                    Some(arity) => {
+                        let elements =
+                            handle_tuple_expression(&let_statement.expression, arity, context)?;
+                        let tuple = context.push_tuple(elements)?;
+                        context.bind(ident.name.clone(), tuple);
+                    }
+                    None => {
+                        let register = handle_let_value(
+                            &let_statement.expression,
+                            let_statement.r#type.as_ref(),
+                            context,
+                        )?;
+                        context.bind(ident.name.clone(), register);
+                    }
+                }
+            }
+            Pattern::Tuple(patterns, _) => {
+                let registers =
+                    handle_tuple_expression(&let_statement.expression, patterns.len(), context)?;
+                for (pattern, register) in patterns.iter().zip(registers) {
+                    match pattern {
+                        Pattern::Identifier(ident) => {
+                            context.bind(ident.name.clone(), register);
+                        }
+                        Pattern::Mutable(_, _) => {
+                            return Err(CompileError::UnsupportedPattern("mutable"))
+                        }
+                        Pattern::Tuple(_, _) => {
+                            return Err(CompileError::UnsupportedPattern("nested tuple"))
+                        }
+                        Pattern::Struct(_, _, _) => {
+                            return Err(CompileError::UnsupportedPattern("struct"))
+                        }
+                    }
+                }
+            }
+            Pattern::Mutable(pattern, _) => match (
+                pattern.as_ref(),
+                &let_statement.r#type,
+                context.target.bounded_vec_capacity,
+            ) {
+                (
+                    Pattern::Identifier(ident),
+                    Some(UnresolvedType::Array(None, element_type)),
+                    Some(capacity),
+                ) => {
+                    let element_type = to_aleo_type(element_type)?;
+                    let elements = (0..capacity)
+                        .map(|_| zero_value(&element_type, context))
+                        .collect::<Result<_, _>>()?;
+                    let length = typed_literal_operand(0, "u32".to_owned(), context);
+                    context.bounded_vecs.insert(
+                        ident.name.clone(),
+                        BoundedVec {
+                            element_type,
+                            elements,
+                            length,
+                        },
+                    );
+                    let ExpressionKind::Literal(Literal::Array(ArrayLiteral::Standard(
+                        initial_elements,
+                    ))) = &let_statement.expression.kind
+                    else {
+                        return Err(CompileError::UnsupportedExpression(
+                            "bounded slice initialized with anything but a slice literal",
+                        ));
+                    };
+                    for element in initial_elements {
+                        compile_bounded_push(&ident.name, element, context)?;
+                    }
+                }
+                _ => return Err(CompileError::UnsupportedPattern("mutable")),
+            },
+            Pattern::Struct(_, _, _) => return Err(CompileError::UnsupportedPattern("struct")),
+        },
+        Statement::Constrain(constrain_statement) => match context.condition.clone() {
+            None => {
+                let assertions = compile_assertions(&constrain_statement.0, context)?;
+                context.function.instructions.extend(assertions);
+            }
+            // A constraint in an `if` only has to hold when its branch is
+            // taken.
+            Some(condition) => {
+                let holds = handle_expression(&constrain_statement.0, context)?;
+                expect_boolean(&holds, context)?;
+                let selected = context.push_operation(
+                    Opcode::Ternary,
+                    vec![condition, holds, Operand::Boolean(true)],
+                    Some("boolean".to_owned()),
+                );
+                context.push_instruction(assert_true(selected, context.target.dialect));
+            }
+        },
+        Statement::Expression(expression) => match return_type {
+            UnresolvedType::Tuple(element_types) if is_tail => {
+                return handle_tuple_expression(expression, element_types.len(), context).map(Some);
+            }
+            _ if !is_tail => handle_discarded_expression(expression, context)?,
+            UnresolvedType::Unit => handle_discarded_expression(expression, context)?,
+            _ => {
+                let literal_type = to_aleo_type(return_type).ok().filter(|_| is_tail);
+                let value = handle_typed_expression(expression, literal_type.as_deref(), context)?;
+                return Ok(is_tail.then(|| vec![value]));
+            }
+        },
+        Statement::Semi(expression) => handle_discarded_expression(expression, context)?,
+        // `xs = xs.push_back(x)`, the way Noir pushes to a slice.
+        Statement::Assign(AssignStatement {
+            lvalue: LValue::Ident(ident),
+            expression,
+        }) if context.bounded_vecs.contains_key(&ident.name) => match &expression.kind {
+            ExpressionKind::MethodCall(method_call)
+                if bounded_vec_of(method_call, context) == Some(&ident.name) =>
+            {
+                compile_bounded_vec_method(method_call, context)?;
+            }
+            _ => return Err(CompileError::UnsupportedStatement("assignment")),
+        },
+        Statement::Assign(_) => return Err(CompileError::UnsupportedStatement("assignment")),
+        Statement::Error => return Err(CompileError::UnsupportedStatement("malformed")),
+    }
+    Ok(None)
+}
+
+/// Compiles the value of a `let` or a global. An annotated type is that of
+/// the unsuffixed integer literals of a value whose type comes from its
+/// surroundings, and must be the type of any other value.
+fn handle_let_value(
+    expression: &Expression,
+    typ: Option<&UnresolvedType>,
+    context: &mut FunctionContext,
+) -> Result<Operand, CompileError> {
+    let Some(typ) = typ else {
+        return handle_expression(expression, context);
+    };
+    let declared = to_aleo_type(typ)?;
+    let operand = handle_typed_expression(expression, Some(&declared), context)?;
+    match context.register_types.get(&operand) {
+        Some(found) if *found != declared => Err(CompileError::TypeMismatch {
+            expected: declared,
+            found: found.clone(),
+        }),
+        Some(_) => Ok(operand),
+        None => {
+            context.register_types.insert(operand.clone(), declared);
+            Ok(operand)
+        }
+    }
+}
+
+/// Compiles an expression of a tuple type into one operand per component.
+/// Tuple literals and calls give their components directly, other values
+/// are read from the struct holding them.
+fn handle_tuple_expression(
+    expression: &Expression,
+    arity: usize,
+    context: &mut FunctionContext,
+) -> Result<Vec<Operand>, CompileError> {
+    match &expression.kind {
+        ExpressionKind::Tuple(elements) => {
+            if elements.len() != arity {
+                return Err(CompileError::TupleArityMismatch {
+                    expected: arity,
+                    found: elements.len(),
+                });
+            }
+            elements
+                .iter()
+                .map(|element| handle_expression(element, context))
+                .collect()
+        }
+        // Tuple parameters are flattened into one input per component,
+        // bound as `name.index`.
+        ExpressionKind::Path(path)
+            if path
+                .as_ident()
+                .is_some_and(|ident| context.lookup(&format!("{}.0", ident.name)).is_some()) =>
+        {
+            let name = &path.as_ident().expect("checked by the guard").name;
+            (0..arity)
+                .map(|index| {
+                    context
+                        .lookup(&format!("{name}.{index}"))
+                        .ok_or_else(|| context.unbound(name, path.span()))
+                })
+                .collect()
+        }
+        ExpressionKind::Call(call) => {
+            let destinations = compile_call(call, context)?;
+            if destinations.len() != arity {
+                return Err(CompileError::TupleArityMismatch {
+                    expected: arity,
+                    found: destinations.len(),
+                });
+            }
+            Ok(destinations)
+        }
+        _ => {
+            let tuple = handle_expression(expression, context)?;
+            context.tuple_elements(&tuple, arity)
+        }
+    }
+}
+
+/// The name of the struct a tuple of `element_types` is lowered to.
+fn tuple_struct_name(element_types: &[String]) -> String {
+    let mut name = format!("Tuple{}", element_types.len());
+    for element_type in element_types {
+        name.push('_');
+        // An array, such as `[u32; 4u32]`, is named `u32x4`.
+        match aleo_array_layout(element_type) {
+            Some((element_type, length)) => name += &format!("{element_type}x{length}"),
+            None => name += element_type,
+        }
+    }
+    name
+}
+
+/// The Aleo member holding the element at `index` of a tuple.
+fn tuple_member(index: usize) -> String {
+    format!("item{index}")
+}
+
+fn tuple_element(tuple: &Operand, index: usize) -> Operand {
+    Operand::Member {
+        base: Box::new(tuple.clone()),
+        member: tuple_member(index),
+    }
+}
+
+/// Compiles the arguments of a call to `callee`. A tuple argument is passed
+/// as one operand per component, since tuple parameters are flattened into
+/// one input per component.
+fn handle_arguments(
+    callee: &FunctionDefinition,
+    arguments: &[Expression],
+    context: &mut FunctionContext,
+) -> Result<Vec<Operand>, CompileError> {
+    let mut operands = Vec::new();
+    for (index, argument) in arguments.iter().enumerate() {
+        match callee.parameters.get(index) {
+            Some((_, UnresolvedType::Tuple(element_types), _)) => operands.extend(
+                handle_tuple_expression(argument, element_types.len(), context)?,
+            ),
+            _ => operands.push(handle_expression(argument, context)?),
+        }
+    }
+    Ok(operands)
+}
+
+/// Compiles an expression whose value is not used. Calls are lowered
+/// directly so that functions without outputs can be called.
+fn handle_discarded_expression(
+    expression: &Expression,
+    context: &mut FunctionContext,
+) -> Result<(), CompileError> {
+    match &expression.kind {
+        ExpressionKind::Call(call) => compile_call(call, context).map(drop),
+        ExpressionKind::MethodCall(method_call)
+            if bounded_vec_of(method_call, context).is_some() =>
+        {
+            compile_bounded_vec_method(method_call, context).map(drop)
+        }
+        ExpressionKind::MethodCall(method_call) => match mapping_of(method_call, context) {
+            Some(mapping) => compile_mapping_operation(method_call, mapping, context).map(drop),
+            None => handle_expression(expression, context).map(drop),
+        },
+        ExpressionKind::For(for_expression) => compile_unrolled_loop(for_expression, context),
+        ExpressionKind::If(if_expression) => compile_conditional(if_expression, context),
+        _ => handle_expression(expression, context).map(drop),
+    }
+}
+
+/// Compiles a `for` loop over a range of integer literals, whose value is
+/// not used, as one copy of its body per iteration. The index is a literal
+/// in each copy, of the type of the bounds or `u32` like array indices.
+fn compile_unrolled_loop(
+    for_expression: &ForExpression,
+    context: &mut FunctionContext,
+) -> Result<(), CompileError> {
+    let bound = |expression: &Expression| match &expression.kind {
+        ExpressionKind::Literal(Literal::Integer(value, typ)) => Ok((*value, typ.clone())),
+        _ => Err(
+            CompileError::UnsupportedExpression("for loop with non-literal bounds")
+                .with_span(expression.span),
+        ),
+    };
+    let (start, start_type) = bound(&for_expression.start_range)?;
+    let (end, end_type) = bound(&for_expression.end_range)?;
+    let index_type = match start_type.or(end_type) {
+        Some(typ) => to_aleo_type(&typ)?,
+        None => "u32".to_owned(),
+    };
+    let ExpressionKind::Block(block) = &for_expression.block.kind else {
+        return Err(CompileError::UnsupportedExpression("for loop"));
+    };
+    for value in start..end {
+        context.register_registry.push_scope();
+        let index = typed_literal_operand(value, index_type.clone(), context);
+        context.bind(for_expression.identifier.name.clone(), index);
+        let result = compile_block_statements(block, context);
+        context.register_registry.pop_scope();
+        result?;
+    }
+    Ok(())
+}
+
+/// Compiles an `if` whose value is not used. Like for a conditional
+/// constraint, both branches are computed whatever the condition, which
+/// only decides the pushes and constraints they make.
+fn compile_conditional(
+    if_expression: &IfExpression,
+    context: &mut FunctionContext,
+) -> Result<(), CompileError> {
+    let condition = handle_expression(&if_expression.condition, context)?;
+    expect_boolean(&condition, context)
+        .map_err(|error| error.with_span(if_expression.condition.span))?;
+    let outer_condition = context.condition.clone();
+    let result =
+        compile_branch(&if_expression.consequence, condition.clone(), context).and_then(|()| {
+            match &if_expression.alternative {
+                Some(alternative) => {
+                    let negated = context.push_operation(
+                        Opcode::Not,
+                        vec![condition],
+                        Some("boolean".to_owned()),
+                    );
+                    compile_branch(alternative, negated, context)
+                }
+                None => Ok(()),
+            }
+        });
+    context.condition = outer_condition;
+    result
+}
+
+/// Compiles a branch of an `if`, taken when `condition` holds within the
+/// branch around it.
+fn compile_branch(
+    branch: &Expression,
+    condition: Operand,
+    context: &mut FunctionContext,
+) -> Result<(), CompileError> {
+    let outer_condition = context.condition.clone();
+    context.condition = Some(match outer_condition.clone() {
+        Some(outer_condition) => context.push_operation(
+            Opcode::And,
+            vec![outer_condition, condition],
+            Some("boolean".to_owned()),
+        ),
+        None => condition,
+    });
+    let first_instruction = context.function.instructions.len();
+    let result = match &branch.kind {
+        ExpressionKind::Block(block) => {
+            context.register_registry.push_scope();
+            let result = compile_block_statements(block, context);
+            context.register_registry.pop_scope();
+            result
+        }
+        // `else if`
+        ExpressionKind::If(if_expression) => compile_conditional(if_expression, context),
+        _ => handle_discarded_expression(branch, context),
+    };
+    context.condition = outer_condition;
+    result?;
+    // Both branches run, so one that can fail would fail the program even
+    // when it is not taken.
+    let composites: HashSet<String> = context
+        .struct_registry
+        .keys()
+        .chain(context.tuple_structs.keys())
+        .cloned()
+        .collect();
+    if context.function.instructions[first_instruction..]
+        .iter()
+        .any(|instruction| optimize::can_fail(instruction, &composites))
+    {
+        return Err(CompileError::FallibleBranch);
+    }
+    Ok(())
+}
+
+/// Compiles the statements of a block whose value is not used.
+fn compile_block_statements(
+    block: &BlockExpression,
+    context: &mut FunctionContext,
+) -> Result<(), CompileError> {
+    for statement in &block.0 {
+        compile_statement(statement, false, &UnresolvedType::Unit, context).map_err(|error| {
+            match statement.span() {
+                Some(span) => error.with_span(span),
+                None => error,
+            }
+        })?;
+    }
+    Ok(())
+}
+
+fn expect_boolean(operand: &Operand, context: &FunctionContext) -> Result<(), CompileError> {
+    match context.register_types.get(operand) {
+        Some(found) if found != "boolean" => Err(CompileError::TypeMismatch {
+            expected: "boolean".to_owned(),
+            found: found.clone(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn as_path_name(expression: &Expression) -> Option<&str> {
+    match &expression.kind {
+        ExpressionKind::Path(path) => Some(&path.as_ident()?.name),
+        _ => None,
+    }
+}
+
+/// The name of the bounded slice a method is called on, if it is one.
+fn bounded_vec_of<'c>(
+    method_call: &MethodCallExpression,
+    context: &'c FunctionContext,
+) -> Option<&'c String> {
+    let ExpressionKind::Path(path) = &method_call.object.kind else {
+        return None;
+    };
+    let (name, _) = context.bounded_vecs.get_key_value(&path.as_ident()?.name)?;
+    Some(name)
+}
+
+/// Compiles `push`, `push_back` or `len` called on a bounded slice,
+/// returning the length for `len`.
+fn compile_bounded_vec_method(
+    method_call: &MethodCallExpression,
+    context: &mut FunctionContext,
+) -> Result<Option<Operand>, CompileError> {
+    let name = bounded_vec_of(method_call, context)
+        .expect("only called on bounded slices")
+        .clone();
+    let method_name = &method_call.method_name.name;
+    if !BOUNDED_VEC_METHODS.contains(&method_name.as_str()) {
+        return Err(CompileError::DynamicCollection(format!(
+            "the method `{method_name}`"
+        )));
+    }
+    let arity = if method_name == "len" { 0 } else { 1 };
+    if method_call.arguments.len() != arity {
+        return Err(CompileError::ArgumentCountMismatch {
+            name: format!("{name}.{method_name}"),
+            expected: arity,
+            found: method_call.arguments.len(),
+        });
+    }
+    if method_name == "len" {
+        return Ok(Some(context.bounded_vecs[&name].length.clone()));
+    }
+    compile_bounded_push(&name, &method_call.arguments[0], context)?;
+    Ok(None)
+}
+
+/// Pushes `value` to the bounded slice `name`, if the statement runs at all.
+/// A push into a full slice fails.
+fn compile_bounded_push(
+    name: &str,
+    value: &Expression,
+    context: &mut FunctionContext,
+) -> Result<(), CompileError> {
+    let bounded_vec = context.bounded_vecs[name].clone();
+    let value = handle_typed_expression(value, Some(&bounded_vec.element_type), context)?;
+    if let Some(found) = context.register_types.get(&value) {
+        if *found != bounded_vec.element_type {
+            return Err(CompileError::TypeMismatch {
+                expected: bounded_vec.element_type,
+                found: found.clone(),
+            });
+        }
+    }
+    // Until the first push in an `if`, the length is a literal, and so is
+    // which element a push writes.
+    let known_length = match bounded_vec.length {
+        Operand::Integer { value, .. } => Some(value),
+        _ => None,
+    };
+    let capacity = bounded_vec.elements.len();
+    let condition = context.condition.clone();
+    if let (Some(length), None) = (known_length, &condition) {
+        if length >= capacity as u128 {
+            return Err(CompileError::IndexOutOfBounds {
+                index: length,
+                length: capacity as u32,
+            });
+        }
+    }
+    let boolean = || Some("boolean".to_owned());
+    let u32_literal = |value: u128, context: &mut FunctionContext| {
+        typed_literal_operand(value, "u32".to_owned(), context)
+    };
+
+    let fits = match known_length {
+        Some(length) => Operand::Boolean(length < capacity as u128),
+        None => {
+            let capacity = u32_literal(capacity as u128, context);
+            context.push_operation(
+                Opcode::Lt,
+                vec![bounded_vec.length.clone(), capacity],
+                boolean(),
+            )
+        }
+    };
+    let holds = match (&condition, fits) {
+        (_, Operand::Boolean(true)) => None,
+        (Some(condition), fits) => Some(context.push_operation(
+            Opcode::Ternary,
+            vec![condition.clone(), fits, Operand::Boolean(true)],
+            boolean(),
+        )),
+        (None, fits) => Some(fits),
+    };
+    if let Some(holds) = holds {
+        context.push_instruction(assert_true(holds, context.target.dialect));
+    }
+
+    let mut elements = Vec::new();
+    for (index, element) in bounded_vec.elements.into_iter().enumerate() {
+        let is_next = match known_length {
+            Some(length) => Operand::Boolean(length == index as u128),
+            None => {
+                let index = u32_literal(index as u128, context);
+                context.push_operation(
+                    Opcode::IsEq,
+                    vec![bounded_vec.length.clone(), index],
+                    boolean(),
+                )
+            }
+        };
+        let is_written = match (&condition, is_next) {
+            (_, Operand::Boolean(false)) => {
+                elements.push(element);
+                continue;
+            }
+            (None, Operand::Boolean(true)) => {
+                elements.push(value.clone());
+                continue;
+            }
+            (Some(condition), Operand::Boolean(true)) => condition.clone(),
+            (Some(condition), is_next) => {
+                context.push_operation(Opcode::And, vec![condition.clone(), is_next], boolean())
+            }
+            (None, is_next) => is_next,
+        };
+        elements.push(context.push_operation(
+            Opcode::Ternary,
+            vec![is_written, value.clone(), element],
+            Some(bounded_vec.element_type.clone()),
+        ));
+    }
+    let next_length = match known_length {
+        Some(length) => u32_literal(length + 1, context),
+        // The length is below the capacity whenever the push happens, and
+        // the sum is discarded otherwise, so it cannot overflow.
+        None => {
+            let one = u32_literal(1, context);
+            context.push_operation(
+                Opcode::AddWrapped,
+                vec![bounded_vec.length.clone(), one],
+                Some("u32".to_owned()),
+            )
+        }
+    };
+    let length = match &condition {
+        Some(condition) => context.push_operation(
+            Opcode::Ternary,
+            vec![condition.clone(), next_length, bounded_vec.length],
+            Some("u32".to_owned()),
+        ),
+        None => next_length,
+    };
+    context.bounded_vecs.insert(
+        name.to_owned(),
+        BoundedVec {
+            element_type: bounded_vec.element_type,
+            elements,
+            length,
+        },
+    );
+    Ok(())
+}
+
+/// The value the elements of a bounded slice hold until they are pushed.
+fn zero_value(aleo_type: &str, context: &mut FunctionContext) -> Result<Operand, CompileError> {
+    match aleo_type {
+        "boolean" => Ok(Operand::Boolean(false)),
+        "field" | "group" | "scalar" => Ok(typed_literal_operand(0, aleo_type.to_owned(), context)),
+        _ if is_aleo_integer_type(aleo_type) => {
+            Ok(typed_literal_operand(0, aleo_type.to_owned(), context))
+        }
+        _ => Err(CompileError::UnsupportedType(format!("[{aleo_type}]"))),
+    }
+}
+
+/// Returns the name and declaration of the mapping a method is called on,
+/// if it is one.
+fn mapping_of(
+    method_call: &MethodCallExpression,
+    context: &FunctionContext,
+) -> Option<(String, AleoMapping)> {
+    let ExpressionKind::Path(path) = &method_call.object.kind else {
+        return None;
+    };
+    let name = &path.as_ident()?.name;
+    let mapping = context.mappings.get(name)?;
+    Some((name.clone(), mapping.clone()))
+}
+
+/// Emits the mapping instruction for a `get`, `set`, `contains` or `remove`
+/// call, returning the destination of those that produce a value. Mappings
+/// are on-chain state, so snarkVM only reads and writes them in `finalize`
+/// blocks.
+fn compile_mapping_operation(
+    method_call: &MethodCallExpression,
+    (name, mapping): (String, AleoMapping),
+    context: &mut FunctionContext,
+) -> Result<Option<Operand>, CompileError> {
+    if context.kind != CallableKind::Finalize {
+        return Err(CompileError::InvalidMapping {
+            name,
+            reason: "mapping operations are only allowed in finalize",
+        });
+    }
+    if context.condition.is_some() {
+        return Err(CompileError::UnsupportedStatement(
+            "conditional mapping operation",
+        ));
+    }
+    let method_name = &method_call.method_name.name;
+    let Some((_, arity)) = MAPPING_METHODS
+        .iter()
+        .find(|(method, _)| method == method_name)
+    else {
+        return Err(CompileError::UnknownMethod {
+            method: method_name.clone(),
+            known_methods: MAPPING_METHODS.iter().map(|(method, _)| *method).collect(),
+        });
+    };
+    if method_call.arguments.len() != *arity {
+        return Err(CompileError::ArgumentCountMismatch {
+            name: format!("{name}.{method_name}"),
+            expected: *arity,
+            found: method_call.arguments.len(),
+        });
+    }
+    let key = mapping_operand(&method_call.arguments[0], &mapping.key_type, context)?;
+    match method_name.as_str() {
+        "get" => {
+            let destination = context.new_register(Some(mapping.value_type.clone()));
+            context.push_instruction(Instruction::GetOrUse {
+                mapping: name,
+                key,
+                default: mapping.default,
+                destination,
+            });
+            Ok(Some(destination.into()))
+        }
+        "contains" => {
+            let destination = context.new_register(Some("boolean".to_owned()));
+            context.push_instruction(Instruction::Contains {
+                mapping: name,
+                key,
+                destination,
+            });
+            Ok(Some(destination.into()))
+        }
+        "set" => {
+            let value = mapping_operand(&method_call.arguments[1], &mapping.value_type, context)?;
+            context.push_instruction(Instruction::Set {
+                value,
+                mapping: name,
+                key,
+            });
+            Ok(None)
+        }
+        _ => {
+            context.push_instruction(Instruction::Remove { mapping: name, key });
+            Ok(None)
+        }
+    }
+}
+
+/// Compiles a mapping key or value, giving unsuffixed integer literals the
+/// mapping's type.
+fn mapping_operand(
+    expression: &Expression,
+    aleo_type: &str,
+    context: &mut FunctionContext,
+) -> Result<Operand, CompileError> {
+    handle_typed_expression(expression, Some(aleo_type), context)
+}
+
+/// Compiles the built-in method `method_name` called on `receiver` with
+/// `arguments` to its Aleo instruction.
+fn compile_builtin_method(
+    method_name: &str,
+    receiver: &Expression,
+    arguments: &[Expression],
+    context: &mut FunctionContext,
+) -> Result<Operand, CompileError> {
+    let Some((method, opcode, arity, wrap_mode)) = BUILTIN_METHODS
+        .iter()
+        .find(|(name, _, _, _)| *name == method_name)
+    else {
+        return Err(CompileError::UnknownMethod {
+            method: method_name.to_owned(),
+            known_methods: BUILTIN_METHODS
+                .iter()
+                .map(|(name, _, _, _)| *name)
+                .collect(),
+        });
+    };
+    if arguments.len() != *arity {
+        return Err(CompileError::ArgumentCountMismatch {
+            name: method_name.to_owned(),
+            expected: *arity,
+            found: arguments.len(),
+        });
+    }
+    let receiver = handle_expression(receiver, context)?;
+    let result_type = context.register_types.get(&receiver).cloned();
+    let argument_type = result_type
+        .as_deref()
+        .and_then(|receiver_type| method_argument_type(opcode, *wrap_mode, receiver_type));
+    let mut operands = vec![receiver];
+    for argument in arguments {
+        operands.push(handle_typed_expression(
+            argument,
+            argument_type.as_deref(),
+            context,
+        )?);
+    }
+    if let Some(receiver_type) = &result_type {
+        let argument_types: Vec<Option<&String>> = operands[1..]
+            .iter()
+            .map(|operand| context.register_types.get(operand))
+            .collect();
+        check_method_operands(method, opcode, *wrap_mode, receiver_type, &argument_types)?;
+    }
+    let opcode = context.target.opcodes.get(&wrap_mode.opcode_name(opcode));
+    Ok(context.push_operation(opcode, operands, result_type))
+}
+
+/// Emits a `call` to another function of this program and returns one
+/// destination register per output of the callee.
+fn compile_call(
+    call: &CallExpression,
+    context: &mut FunctionContext,
+) -> Result<Vec<Operand>, CompileError> {
+    let ExpressionKind::Path(path) = &call.func.kind else {
+        return Err(CompileError::UnsupportedExpression("indirect call"));
+    };
+    let path_name = path.to_string();
+    if let Some(intrinsic) = context.target.intrinsics.get(&path_name) {
+        return compile_intrinsic_call(&path_name, &intrinsic, call, context)
+            .map(|result| vec![result]);
+    }
+    if let Some(method) = path_name
+        .strip_prefix("std::")
+        .filter(|name| WRAPPING_FUNCTIONS.contains(name))
+    {
+        let [lhs, rhs] = call.arguments.as_slice() else {
+            return Err(CompileError::ArgumentCountMismatch {
+                name: path_name,
+                expected: 2,
+                found: call.arguments.len(),
+            });
+        };
+        return compile_builtin_method(method, lhs, std::slice::from_ref(rhs), context)
+            .map(|result| vec![result]);
+    }
+    if path_name.starts_with("std::") {
+        let reason = UNMAPPED_INTRINSICS
+            .iter()
+            .find(|(unmapped_path, _)| *unmapped_path == path_name)
+            .map(|(_, reason)| *reason);
+        return Err(CompileError::NoAleoEquivalent {
+            path: path_name,
+            reason,
+        });
+    }
+    let Some(ident) = path.as_ident() else {
+        return Err(CompileError::UnsupportedExternalCall(path.to_string()));
+    };
+    let callee = context
+        .noir_ast
+        .functions
+        .iter()
+        .find(|function| function.name.name == ident.name)
+        .ok_or_else(|| CompileError::UndeclaredFunction(ident.name.clone()))?;
+    // Aleo calls always run, so one in an `if` would run whatever the
+    // condition.
+    if context.condition.is_some() {
+        return Err(CompileError::UnsupportedStatement("conditional call"));
+    }
+    if is_finalize(callee) {
+        if context.finalize.is_some() {
+            return Err(CompileError::InvalidFinalize {
+                function: ident.name.clone(),
+                reason: "a function can only finalize once",
+            });
+        }
+        let operands = handle_arguments(callee, &call.arguments, context)?;
+        // The `finalize` command has to come after the outputs, so it is
+        // emitted once the rest of the function is.
+        context.finalize = Some((callee, operands));
+        return Ok(Vec::new());
+    }
+    let output_types = match &callee.return_type {
+        UnresolvedType::Tuple(element_types) => element_types
+            .iter()
+            .map(to_aleo_type)
+            .collect::<Result<Vec<_>, _>>()?,
+        UnresolvedType::Unit => Vec::new(),
+        return_type => vec![to_aleo_type(return_type)?],
+    };
+
+    let arguments = handle_arguments(callee, &call.arguments, context)?;
+    let destinations: Vec<Register> = output_types
+        .into_iter()
+        .map(|aleo_type| context.new_register(Some(aleo_type)))
+        .collect();
+    let operand_types = arguments
+        .iter()
+        .map(|argument| context.register_types.get(argument).cloned())
+        .collect();
+    context.push_instruction(Instruction::Call {
+        callee: ident.name.clone(),
+        operands: arguments,
+        destinations: destinations.clone(),
+    });
+    context.call_sites.push(CallSite {
+        caller: context.function_name.clone(),
+        callee: ident.name.clone(),
+        operand_types,
+        span: call.func.span,
+    });
+    Ok(destinations.into_iter().map(Operand::from).collect())
+}
+
+/// Emits the single instruction `intrinsic`, called through `path`, is
+/// compiled to, checking its arguments against what the instruction accepts.
+fn compile_intrinsic_call(
+    path: &str,
+    intrinsic: &Intrinsic,
+    call: &CallExpression,
+    context: &mut FunctionContext,
+) -> Result<Operand, CompileError> {
+    if call.arguments.len() != intrinsic.arguments.len() {
+        return Err(CompileError::ArgumentCountMismatch {
+            name: path.to_owned(),
+            expected: intrinsic.arguments.len(),
+            found: call.arguments.len(),
+        });
+    }
+    let mut operands = Vec::new();
+    for (argument, accepted) in call.arguments.iter().zip(&intrinsic.arguments) {
+        let operand = handle_expression(argument, context)?;
+        if let Some(found) = context.register_types.get(&operand) {
+            if !accepted.is_empty() && !accepted.contains(found) {
+                return Err(CompileError::InvalidIntrinsicArgument {
+                    intrinsic: path.to_owned(),
+                    found: found.clone(),
+                    accepted: accepted.clone(),
+                });
+            }
+        }
+        operands.push(operand);
+    }
+    let result_type = intrinsic.result_type.clone();
+    if !intrinsic.opcode.declares_destination_type() {
+        return Ok(context.push_operation(intrinsic.opcode.clone(), operands, Some(result_type)));
+    }
+    let destination = context.new_register(Some(result_type.clone()));
+    context.push_instruction(Instruction::Hash {
+        opcode: intrinsic.opcode.clone(),
+        operands,
+        destination,
+        typ: result_type,
+    });
+    Ok(destination.into())
+}
+
+/// Adds the inputs of a parameter and binds the names it declares to them.
+fn compile_parameter(
+    pattern: &Pattern,
+    typ: &UnresolvedType,
+    visibility: &AbiFEType,
+    context: &mut FunctionContext,
+) -> Result<(), CompileError> {
+    check_declared_types(typ, context.struct_registry)?;
+    // Tuples are flattened into one input per component.
+    if let UnresolvedType::Tuple(element_types) = typ {
+        let element_patterns: Vec<Pattern> = match pattern {
+            Pattern::Identifier(ident) => (0..element_types.len())
+                .map(|index| {
+                    Pattern::Identifier(Ident {
+                        name: format!("{}.{index}", ident.name),
+                        span: ident.span,
+                    })
+                })
+                .collect(),
+            Pattern::Tuple(patterns, _) => patterns.clone(),
+            Pattern::Mutable(_, _) => return Err(CompileError::UnsupportedPattern("mutable")),
+            Pattern::Struct(_, _, _) => return Err(CompileError::UnsupportedPattern("struct")),
+        };
+        if element_patterns.len() != element_types.len() {
+            return Err(CompileError::TupleArityMismatch {
+                expected: element_types.len(),
+                found: element_patterns.len(),
+            });
+        }
+        for (element_pattern, element_type) in element_patterns.iter().zip(element_types) {
+            compile_parameter(element_pattern, element_type, visibility, context)?;
+        }
+        return Ok(());
+    }
+
+    let aleo_type = to_aleo_type(typ)?;
+    let register = context.new_register(Some(aleo_type.clone()));
+    match pattern {
+        Pattern::Identifier(ident) => {
+            context.bind(ident.name.clone(), register.into());
+        }
+        Pattern::Mutable(_, _) => return Err(CompileError::UnsupportedPattern("mutable")),
+        Pattern::Tuple(_, _) => return Err(CompileError::UnsupportedPattern("tuple")),
+        Pattern::Struct(_, _, _) => return Err(CompileError::UnsupportedPattern("struct")),
+    }
+    let input = match context.declared_type(&aleo_type) {
+        Some(record_type) => Input {
+            register,
+            typ: record_type,
+            visibility: None,
+        },
+        None => Input {
+            register,
+            typ: aleo_type,
+            visibility: context.visibility(visibility),
+        },
+    };
+    context.function.inputs.push(input);
+    Ok(())
+}
+
+/// Lowers the expression of a `constrain` statement into Aleo assertions.
+/// The operands are compiled first, which may append their instructions to
+/// the function, and the assertions are returned to be emitted after them.
+fn compile_assertions(
+    constraint: &Expression,
+    context: &mut FunctionContext,
+) -> Result<Vec<Instruction>, CompileError> {
+    match &constraint.kind {
+        // Each conjunct of a top-level `&&` chain gets its own assertion, which
+        // needs no register for the intermediate `and`.
+        ExpressionKind::Infix(infix) if infix.operator == BinaryOpKind::And => {
+            let mut assertions = compile_assertions(&infix.lhs, context)?;
+            assertions.extend(compile_assertions(&infix.rhs, context)?);
+            Ok(assertions)
+        }
+        ExpressionKind::Infix(infix) => match to_aleo_assertion(infix.operator) {
+            Some(opcode) => {
+                let (lhs, rhs) = handle_infix_operands(infix, context)?;
+                // The operands are checked as those of the comparison the
+                // assertion stands for.
+                to_aleo_opcode(
+                    infix.operator,
+                    context.register_types.get(&lhs).map(String::as_str),
+                    context.register_types.get(&rhs).map(String::as_str),
+                )?;
+                Ok(vec![Instruction::Assert { opcode, lhs, rhs }])
+            }
+            None => compile_boolean_assertion(constraint, context),
+        },
+        // A conditional constraint only enforces the branch that is taken:
+        // both branches are evaluated as booleans, the taken one is selected
+        // with `ternary` and only the selection is asserted.
+        ExpressionKind::If(if_expression) => {
+            let condition = handle_expression(&if_expression.condition, context)?;
+            let consequence = handle_expression(block_value(&if_expression.consequence)?, context)?;
+            let alternative = match &if_expression.alternative {
+                Some(alternative) => handle_expression(block_value(alternative)?, context)?,
+                None => Operand::Boolean(true),
+            };
+            let selected = context.push_operation(
+                Opcode::Ternary,
+                vec![condition, consequence, alternative],
+                Some("boolean".to_owned()),
+            );
+            Ok(vec![assert_true(selected, context.target.dialect)])
+        }
+        _ => compile_boolean_assertion(constraint, context),
+    }
+}
+
+/// Asserts a constraint that is a boolean expression, computed like any other
+/// expression, such as a comparison with `lt`.
+fn compile_boolean_assertion(
+    constraint: &Expression,
+    context: &mut FunctionContext,
+) -> Result<Vec<Instruction>, CompileError> {
+    let operand = handle_expression(constraint, context)?;
+    if let Some(operand_type) = context.register_types.get(&operand) {
+        if operand_type != "boolean" {
+            return Err(CompileError::TypeMismatch {
+                expected: "boolean".to_owned(),
+                found: operand_type.clone(),
+            });
+        }
+    }
+    Ok(vec![assert_true(operand, context.target.dialect)])
+}
+
+/// Asserts that `operand` is true, in the form `dialect` has for it.
+fn assert_true(operand: Operand, dialect: Dialect) -> Instruction {
+    if dialect.has_boolean_assert() {
+        return Instruction::AssertTrue { operand };
+    }
+    Instruction::Assert {
+        opcode: Opcode::AssertEq,
+        lhs: operand,
+        rhs: Operand::Boolean(true),
+    }
+}
+
+/// Compiles both operands of an infix expression. Aleo literals always carry
+/// their type, so an operand made only of unsuffixed integer literals takes
+/// the type of the other operand, except for shift amounts which Aleo wants
+/// as `u32`. When both are, they take the type their surroundings give the
+/// whole expression.
+fn handle_infix_operands(
+    infix: &InfixExpression,
+    context: &mut FunctionContext,
+) -> Result<(Operand, Operand), CompileError> {
+    let is_shift = matches!(
+        infix.operator,
+        BinaryOpKind::ShiftLeft | BinaryOpKind::ShiftRight
+    );
+    let surrounding_type = context.literal_type.clone();
+    let lhs_is_untyped = is_untyped(&infix.lhs, context);
+    let rhs_is_untyped = is_untyped(&infix.rhs, context);
+    if is_shift || (rhs_is_untyped && !lhs_is_untyped) {
+        let lhs = handle_typed_expression(&infix.lhs, surrounding_type.as_deref(), context)?;
+        let rhs_type = if is_shift {
+            Some("u32".to_owned())
+        } else {
+            context.register_types.get(&lhs).cloned()
+        };
+        let rhs = handle_typed_expression(&infix.rhs, rhs_type.as_deref(), context)?;
+        return Ok((lhs, rhs));
+    }
+    if lhs_is_untyped && !rhs_is_untyped {
+        let rhs = handle_typed_expression(&infix.rhs, None, context)?;
+        let lhs_type = context.register_types.get(&rhs).cloned();
+        let lhs = handle_typed_expression(&infix.lhs, lhs_type.as_deref(), context)?;
+        return Ok((lhs, rhs));
+    }
+    Ok((
+        handle_typed_expression(&infix.lhs, surrounding_type.as_deref(), context)?,
+        handle_typed_expression(&infix.rhs, surrounding_type.as_deref(), context)?,
+    ))
+}
+
+/// Whether the type of `expression` comes from its surroundings rather than
+/// from itself: it is made only of unsuffixed integer literals, and of
+/// globals defined as such, with arithmetic.
+fn is_untyped(expression: &Expression, context: &FunctionContext) -> bool {
+    is_untyped_within(expression, context, &mut Vec::new())
+}
+
+fn is_untyped_within<'a>(
+    expression: &Expression,
+    context: &FunctionContext<'a>,
+    globals: &mut Vec<&'a str>,
+) -> bool {
+    match &expression.kind {
+        ExpressionKind::Literal(Literal::Integer(_, None)) => true,
+        ExpressionKind::Infix(infix) => match infix.operator {
+            BinaryOpKind::ShiftLeft | BinaryOpKind::ShiftRight => {
+                is_untyped_within(&infix.lhs, context, globals)
+            }
+            operator if operator.is_comparator() => false,
+            _ => {
+                is_untyped_within(&infix.lhs, context, globals)
+                    && is_untyped_within(&infix.rhs, context, globals)
+            }
+        },
+        ExpressionKind::Prefix(prefix) => {
+            prefix.operator == UnaryOp::Minus && is_untyped_within(&prefix.rhs, context, globals)
+        }
+        ExpressionKind::Path(path) => {
+            let Some(ident) = path.as_ident() else {
+                return false;
+            };
+            let Some(global) = context.global(&ident.name) else {
+                return false;
+            };
+            // A cyclic global is reported when it is compiled.
+            if context.register_registry.get(&ident.name).is_some()
+                || global.r#type.is_some()
+                || globals.contains(&global.name.name.as_str())
+            {
+                return false;
+            }
+            globals.push(&global.name.name);
+            let is_untyped = is_untyped_within(&global.expression, context, globals);
+            globals.pop();
+            is_untyped
+        }
+        _ => false,
+    }
+}
+
+/// Compiles `expression`, giving its unsuffixed integer literals the type
+/// `literal_type` if its type comes from its surroundings.
+fn handle_typed_expression(
+    expression: &Expression,
+    literal_type: Option<&str>,
+    context: &mut FunctionContext,
+) -> Result<Operand, CompileError> {
+    let literal_type = literal_type
+        .filter(|_| is_untyped(expression, context))
+        .map(str::to_owned);
+    let surrounding_type = mem::replace(&mut context.literal_type, literal_type);
+    let operand = handle_expression(expression, context);
+    context.literal_type = surrounding_type;
+    operand
+}
+
+/// Compiles the value of a global where it is read. Each read computes it
+/// again, and folding turns values made of literals into literals.
+fn handle_global(
+    global: &GlobalDefinition,
+    context: &mut FunctionContext,
+) -> Result<Operand, CompileError> {
+    let name = &global.name.name;
+    if context.expanding_globals.contains(name) {
+        return Err(CompileError::CyclicGlobal(name.clone()));
+    }
+    context.expanding_globals.push(name.clone());
+    let operand = handle_let_value(&global.expression, global.r#type.as_ref(), context);
+    context.expanding_globals.pop();
+    operand.map_err(|error| error.with_span(global.span))
+}
+
+fn typed_literal_operand(value: u128, aleo_type: String, context: &mut FunctionContext) -> Operand {
+    let operand = Operand::Integer {
+        value,
+        typ: aleo_type.clone(),
+    };
+    context.register_types.insert(operand.clone(), aleo_type);
+    operand
+}
+
+/// Compiles the statements of a block, returning the operand holding the
+/// value of its tail expression.
+fn handle_block(
+    block: &BlockExpression,
+    context: &mut FunctionContext,
+) -> Result<Operand, CompileError> {
+    let Some((tail, statements)) = block.0.split_last() else {
+        return Err(CompileError::UnsupportedExpression("value-less block"));
+    };
+    for statement in statements {
+        compile_statement(statement, false, &UnresolvedType::Unit, context)?;
+    }
+    let Statement::Expression(tail) = tail else {
+        return Err(CompileError::UnsupportedExpression("value-less block"));
+    };
+    handle_expression(tail, context)
+}
+
+/// Returns the expression a single-expression block evaluates to.
+fn block_value(expression: &Expression) -> Result<&Expression, CompileError> {
+    match &expression.kind {
+        ExpressionKind::Block(block) => match block.0.as_slice() {
+            [Statement::Expression(value)] => Ok(value),
+            _ => Err(CompileError::UnsupportedExpression("multi-statement block")),
+        },
+        _ => Ok(expression),
+    }
+}
+
+/// Compiles an expression, appending any instructions it needs to the
+/// function, and returns the operand holding its value. Errors point at the
+/// innermost expression that caused them.
+fn handle_expression(
+    expression: &Expression,
+    context: &mut FunctionContext,
+) -> Result<Operand, CompileError> {
+    handle_expression_kind(expression, context).map_err(|error| error.with_span(expression.span))
+}
+
+fn handle_expression_kind(
+    expression: &Expression,
+    context: &mut FunctionContext,
+) -> Result<Operand, CompileError> {
+    match &expression.kind {
+        ExpressionKind::Path(path) => {
+            let name = &path
+                .as_ident()
+                .ok_or_else(|| CompileError::UndeclaredVariable(path.to_string()))?
+                .name;
+            if let Some(bounded_vec) = context.bounded_vecs.get(name) {
+                let array_type = format!(
+                    "[{}; {}u32]",
+                    bounded_vec.element_type,
+                    bounded_vec.elements.len()
+                );
+                let elements = bounded_vec.elements.clone();
+                return Ok(context.push_cast(elements, Some(array_type)));
+            }
+            if let Some(operand) = context.lookup(name) {
+                return Ok(operand);
+            }
+            match context.global(name) {
+                Some(global) => handle_global(global, context),
+                None => Err(context.unbound(name, expression.span)),
+            }
+        }
+        ExpressionKind::Infix(infix) => {
+            let (lhs, rhs) = handle_infix_operands(infix, context)?;
+            let lhs_type = context.register_types.get(&lhs).map(String::as_str);
+            let rhs_type = context.register_types.get(&rhs).map(String::as_str);
+            let opcode = to_aleo_opcode(infix.operator, lhs_type, rhs_type)?;
+            let result_type = if infix.operator.is_comparator() {
+                Some("boolean".to_owned())
+            } else if is_scalar_multiplication(infix.operator, lhs_type, rhs_type) {
+                Some("group".to_owned())
+            } else {
+                lhs_type.or(rhs_type).map(str::to_owned)
+            };
+            let opcode = context.target.opcodes.get(opcode);
+            let result = context.push_operation(opcode, vec![lhs, rhs], result_type);
+            if let (BinaryOpKind::Divide | BinaryOpKind::Modulo, Some(register)) =
+                (infix.operator, result.as_register())
+            {
+                context
+                    .function
+                    .division_spans
+                    .insert(*register, expression.span);
+            }
+            Ok(result)
+        }
+        ExpressionKind::Literal(Literal::Integer(value, typ)) => {
+            let aleo_type = match typ {
+                Some(typ) => to_aleo_type(typ)?,
+                None => context
+                    .literal_type
+                    .clone()
+                    .ok_or(CompileError::UnsupportedExpression(
+                        "unsuffixed integer literal",
+                    ))?,
+            };
+            if optimize::max_value(&aleo_type).is_some_and(|max| *value > max) {
+                return Err(CompileError::IntegerLiteralOutOfRange {
+                    value: *value,
+                    typ: aleo_type,
+                });
+            }
+            Ok(typed_literal_operand(*value, aleo_type, context))
+        }
+        ExpressionKind::Literal(Literal::Array(ArrayLiteral::Standard(elements))) => {
+            if elements.is_empty() {
+                return Err(CompileError::EmptyArrayLiteral);
+            }
+            let mut operands = Vec::new();
+            let mut element_type: Option<String> = None;
+            for element in elements {
+                let operand = handle_expression(element, context)?;
+                if let Some(found) = context.register_types.get(&operand) {
+                    match &element_type {
+                        Some(expected) if expected != found => {
+                            return Err(CompileError::ArrayElementTypeMismatch {
+                                expected: expected.clone(),
+                                found: found.clone(),
+                            });
+                        }
+                        Some(_) => {}
+                        None => element_type = Some(found.clone()),
+                    }
+                }
+                operands.push(operand);
+            }
+            let array_type =
+                element_type.map(|element_type| format!("[{element_type}; {}u32]", operands.len()));
+            Ok(context.push_cast(operands, array_type))
+        }
+        ExpressionKind::Literal(Literal::Array(ArrayLiteral::Repeated { .. })) => Err(
+            CompileError::UnsupportedExpression("repeated array literal"),
+        ),
+        ExpressionKind::Literal(Literal::Bool(_)) => {
+            Err(CompileError::UnsupportedExpression("boolean literal"))
+        }
+        ExpressionKind::Literal(Literal::Str(_)) => {
+            Err(CompileError::UnsupportedExpression("string literal"))
+        }
+        ExpressionKind::Block(block) => {
+            context.register_registry.push_scope();
+            let value = handle_block(block, context);
+            context.register_registry.pop_scope();
+            value
+        }
+        ExpressionKind::Prefix(prefix) => {
+            let operand = handle_expression(&prefix.rhs, context)?;
+            let operand_type = context.register_types.get(&operand).cloned();
+            let (opcode, opcode_name) = match prefix.operator {
+                UnaryOp::Minus => (Opcode::Neg, "neg"),
+                UnaryOp::Not => (Opcode::Not, "not"),
+            };
+            if let Some(operand_type) = &operand_type {
+                if prefix.operator == UnaryOp::Minus && operand_type.starts_with('u') {
+                    return Err(CompileError::UnsignedNegation(operand_type.clone()));
+                }
+                let is_supported = match prefix.operator {
+                    UnaryOp::Minus => {
+                        !matches!(operand_type.as_str(), "boolean" | "scalar" | "address")
+                    }
+                    UnaryOp::Not => !matches!(
+                        operand_type.as_str(),
+                        "field" | "group" | "scalar" | "address"
+                    ),
+                };
+                if !is_supported {
+                    return Err(CompileError::UnsupportedUnaryOperand {
+                        opcode: opcode_name,
+                        operand_type: operand_type.clone(),
+                    });
+                }
+            }
+            Ok(context.push_operation(opcode, vec![operand], operand_type))
+        }
+        ExpressionKind::Index(index) => {
+            // The index of an unrolled loop is a literal too.
+            let position = match &index.index.kind {
+                ExpressionKind::Literal(Literal::Integer(position, _)) => *position,
+                ExpressionKind::Path(_) => match handle_expression(&index.index, context)? {
+                    Operand::Integer { value, .. } => value,
+                    _ => return Err(CompileError::DynamicArrayIndex),
+                },
+                _ => return Err(CompileError::DynamicArrayIndex),
+            };
+            if let Some(bounded_vec) =
+                as_path_name(&index.collection).and_then(|name| context.bounded_vecs.get(name))
+            {
+                let capacity = bounded_vec.elements.len();
+                let element = usize::try_from(position)
+                    .ok()
+                    .and_then(|index| bounded_vec.elements.get(index))
+                    .ok_or(CompileError::IndexOutOfBounds {
+                        index: position,
+                        length: capacity as u32,
+                    })?;
+                return Ok(element.clone());
+            }
+            let collection = handle_expression(&index.collection, context)?;
+            let layout = context
+                .register_types
+                .get(&collection)
+                .and_then(|aleo_type| aleo_array_layout(aleo_type))
+                .map(|(element_type, length)| (element_type.to_owned(), length));
+            let operand = Operand::Index {
+                base: Box::new(collection),
+                index: position,
+            };
+            if let Some((element_type, length)) = layout {
+                if position >= u128::from(length) {
+                    return Err(CompileError::IndexOutOfBounds {
+                        index: position,
+                        length,
+                    });
+                }
+                context.register_types.insert(operand.clone(), element_type);
+            }
+            Ok(operand)
+        }
+        ExpressionKind::Call(call) => {
+            let mut destinations = compile_call(call, context)?;
+            match destinations.len() {
+                0 => Err(CompileError::TupleArityMismatch {
+                    expected: 1,
+                    found: 0,
+                }),
+                1 => Ok(destinations.remove(0)),
+                // The outputs of a function returning a tuple are gathered
+                // into one where it is used as a single value.
+                _ => context.push_tuple(destinations),
+            }
+        }
+        ExpressionKind::MethodCall(method_call) => {
+            if bounded_vec_of(method_call, context).is_some() {
+                return compile_bounded_vec_method(method_call, context)?.ok_or(
+                    CompileError::UnsupportedExpression("value of a push to a bounded slice"),
+                );
+            }
+            if let Some(mapping) = mapping_of(method_call, context) {
+                let destination = compile_mapping_operation(method_call, mapping, context)?.ok_or(
+                    CompileError::UnsupportedExpression("value-less mapping update"),
+                )?;
+                return Ok(destination);
+            }
+            let method_name = &method_call.method_name.name;
+            if DYNAMIC_COLLECTION_METHODS.contains(&method_name.as_str()) {
+                return Err(CompileError::DynamicCollection(format!(
+                    "the method `{method_name}`"
+                )));
+            }
+            compile_builtin_method(
+                method_name,
+                &method_call.object,
+                &method_call.arguments,
+                context,
+            )
+        }
+        ExpressionKind::Constructor(constructor) => {
+            let struct_name = &constructor.type_name.last_segment().name;
+            let noir_struct = *context
+                .struct_registry
+                .get(struct_name)
+                .ok_or_else(|| CompileError::UndeclaredStruct(struct_name.clone()))?;
+            if let Some((extra_field, _)) = constructor.fields.iter().find(|(name, _)| {
+                !noir_struct
+                    .fields
+                    .iter()
+                    .any(|(field_name, _)| field_name.name == name.name)
+            }) {
+                return Err(CompileError::UnknownStructField {
+                    struct_name: struct_name.clone(),
+                    field: extra_field.name.clone(),
+                });
+            }
+            // Aleo builds structs positionally, so the operands follow the
+            // declaration order rather than the order written in the source.
+            let mut operands = Vec::new();
+            for (field_name, _) in &noir_struct.fields {
+                let (_, field_expression) = constructor
+                    .fields
+                    .iter()
+                    .find(|(name, _)| name.name == field_name.name)
+                    .ok_or_else(|| CompileError::MissingStructField {
+                        struct_name: struct_name.clone(),
+                        field: field_name.name.clone(),
+                    })?;
+                operands.push(handle_expression(field_expression, context)?);
+            }
+            Ok(context.push_cast(operands, Some(struct_name.clone())))
+        }
+        ExpressionKind::MemberAccess(member_access) => {
+            let field = &member_access.rhs.name;
+            // Tuples are flattened, so `t.0` names the register of a component.
+            if let ExpressionKind::Path(path) = &member_access.lhs.kind {
+                if let Some(ident) = path.as_ident() {
+                    let component = format!("{}.{field}", ident.name);
+                    if let Some(register) = context.lookup(&component) {
+                        return Ok(register);
+                    }
+                }
+            }
+            let object = handle_expression(&member_access.lhs, context)?;
+            let struct_name = context.register_types.get(&object).cloned();
+            if let Some((struct_name, element_types)) = struct_name
+                .as_ref()
+                .and_then(|struct_name| context.tuple_structs.get_key_value(struct_name))
+            {
+                let (index, element_type) = field
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| Some((index, element_types.get(index)?.clone())))
+                    .ok_or_else(|| CompileError::UnknownStructField {
+                        struct_name: struct_name.clone(),
+                        field: field.clone(),
+                    })?;
+                let operand = tuple_element(&object, index);
+                context.register_types.insert(operand.clone(), element_type);
+                return Ok(operand);
+            }
+            let operand = Operand::Member {
+                base: Box::new(object),
+                member: field.clone(),
+            };
+            if let Some(noir_struct) = struct_name
+                .as_ref()
+                .and_then(|struct_name| context.struct_registry.get(struct_name))
+            {
+                let (_, field_type) = noir_struct
+                    .fields
+                    .iter()
+                    .find(|(name, _)| &name.name == field)
+                    .ok_or_else(|| CompileError::UnknownStructField {
+                        struct_name: noir_struct.name.name.clone(),
+                        field: field.clone(),
+                    })?;
+                let field_type = to_aleo_type(field_type)?;
+                context.register_types.insert(operand.clone(), field_type);
+            }
+            Ok(operand)
+        }
+        ExpressionKind::Cast(cast) => {
+            let source = handle_expression(&cast.lhs, context)?;
+            let destination_type = to_aleo_type(&cast.r#type)?;
+            let mut operands = vec![source.clone()];
+            if let Some(source_type) = context.register_types.get(&source) {
+                if let Some(fields) = struct_conversion_fields(
+                    context.struct_registry,
+                    source_type,
+                    &destination_type,
+                ) {
+                    operands = fields
+                        .iter()
+                        .map(|field| Operand::Member {
+                            base: Box::new(source.clone()),
+                            member: field.to_string(),
+                        })
+                        .collect();
+                } else if !is_supported_cast(source_type, &destination_type) {
+                    return Err(CompileError::UnsupportedCast {
+                        from: source_type.clone(),
+                        to: destination_type,
+                    });
+                }
+            }
+            Ok(context.push_cast(operands, Some(destination_type)))
+        }
+        ExpressionKind::If(_) => Err(CompileError::UnsupportedExpression("if")),
+        ExpressionKind::For(_) => Err(CompileError::UnsupportedExpression("for loop")),
+        ExpressionKind::Tuple(elements) => {
+            let mut operands = Vec::new();
+            for element in elements {
+                operands.push(handle_expression(element, context)?);
+            }
+            context.push_tuple(operands)
+        }
+        ExpressionKind::Error => Err(CompileError::UnsupportedExpression("malformed")),
+    }
+}
+
+fn is_aleo_integer_type(aleo_type: &str) -> bool {
+    matches!(
+        aleo_type,
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128"
+    )
+}
+
+/// Aleo's `cast` converts between integers, fields and booleans, but a
+/// boolean can only be produced from another boolean.
+fn is_supported_cast(source_type: &str, destination_type: &str) -> bool {
+    let is_primitive = |aleo_type: &str| {
+        is_aleo_integer_type(aleo_type) || matches!(aleo_type, "field" | "boolean")
+    };
+    if !is_primitive(source_type) || !is_primitive(destination_type) {
+        return false;
+    }
+    destination_type != "boolean" || source_type == "boolean"
+}
+
+/// Aleo only builds structs from their members, so a struct converts into
+/// another one with the same field types by passing the fields of the source
+/// in order. Returns those fields when the conversion is possible.
+fn struct_conversion_fields<'a>(
+    struct_registry: &StructRegistry<'a>,
+    source_type: &str,
+    destination_type: &str,
+) -> Option<Vec<&'a str>> {
+    let source = struct_registry.get(source_type)?;
+    let destination = struct_registry.get(destination_type)?;
+    let source_field_types = source.fields.iter().map(|(_, typ)| typ);
+    let destination_field_types = destination.fields.iter().map(|(_, typ)| typ);
+    if !source_field_types.eq(destination_field_types) {
+        return None;
+    }
+    Some(
+        source
+            .fields
+            .iter()
+            .map(|(name, _)| name.name.as_str())
+            .collect(),
+    )
+}
+
+fn to_aleo_type(typ: &UnresolvedType) -> Result<String, CompileError> {
+    let aleo_type = match typ {
+        UnresolvedType::FieldElement => "field".to_owned(),
+        UnresolvedType::Integer(_, num_bits) if !ALEO_INTEGER_WIDTHS.contains(num_bits) => {
+            return Err(CompileError::UnsupportedIntegerWidth(typ.to_string()))
+        }
+        UnresolvedType::Integer(Signedness::Unsigned, num_bits) => format!("u{num_bits}"),
+        UnresolvedType::Integer(Signedness::Signed, num_bits) => format!("i{num_bits}"),
+        UnresolvedType::Bool => "boolean".to_owned(),
+        UnresolvedType::Array(Some(length), element_type) => match &length.kind {
+            ExpressionKind::Literal(Literal::Integer(value, _)) => {
+                if u32::try_from(*value).is_err() {
+                    return Err(CompileError::IntegerLiteralOutOfRange {
+                        value: *value,
+                        typ: "u32".to_owned(),
+                    }
+                    .with_span(length.span));
+                }
+                format!("[{}; {value}u32]", to_aleo_type(element_type)?)
+            }
+            _ => return Err(CompileError::NonLiteralArrayLength(length.span)),
+        },
+        // Tuples are flattened by the callers that support them.
+        UnresolvedType::Array(None, _) => {
+            return Err(CompileError::DynamicCollection(format!(
+                "the slice type `{typ}`"
+            )))
+        }
+        UnresolvedType::Unit | UnresolvedType::Tuple(_) | UnresolvedType::Error => {
+            return Err(CompileError::UnsupportedType(typ.to_string()))
+        }
+        UnresolvedType::Named(path) => {
+            let name = &path.last_segment().name;
+            match ALEO_NATIVE_TYPES
+                .iter()
+                .find(|(noir_name, _)| path.as_ident().is_some() && noir_name == name)
+            {
+                Some((_, aleo_type)) => aleo_type.to_string(),
+                None => name.clone(),
+            }
+        }
+    };
+    Ok(aleo_type)
+}
+
+/// Rejects the named types in `typ` that are neither native Aleo types nor
+/// structs or records of the program, pointing at the first of them.
+fn check_declared_types(
+    typ: &UnresolvedType,
+    struct_registry: &StructRegistry,
+) -> Result<(), CompileError> {
+    match typ {
+        UnresolvedType::Array(_, element_type) => {
+            check_declared_types(element_type, struct_registry)
+        }
+        UnresolvedType::Tuple(element_types) => element_types
+            .iter()
+            .try_for_each(|element_type| check_declared_types(element_type, struct_registry)),
+        UnresolvedType::Named(path) => {
+            let name = &path.last_segment().name;
+            let is_native = path.as_ident().is_some()
+                && ALEO_NATIVE_TYPES
+                    .iter()
+                    .any(|(noir_name, _)| noir_name == name);
+            if is_native || struct_registry.contains_key(name) {
+                Ok(())
+            } else {
+                Err(CompileError::UndeclaredStruct(name.clone()).with_span(path.span()))
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Splits an Aleo array type such as `[u32; 4u32]` into its element type and
+/// length.
+fn aleo_array_layout(aleo_type: &str) -> Option<(&str, u32)> {
+    let inner = aleo_type.strip_prefix('[')?.strip_suffix("u32]")?;
+    let (element_type, length) = inner.rsplit_once("; ")?;
+    Some((element_type, length.parse().ok()?))
+}
+
+fn to_aleo_visibility(visibility: &AbiFEType) -> Visibility {
+    match visibility {
+        AbiFEType::Public => Visibility::Public,
+        AbiFEType::Private => Visibility::Private,
+    }
+}
+
+/// The Aleo opcode computing `operator` on operands of `operand_type`. Field
+/// elements are not integers: their `div` multiplies by the inverse of the
+/// divisor, and the remainder, bitwise and shift operators do not exist.
+/// Nor do they on group elements, which cannot be divided either. Addresses
+/// can only be compared for equality.
+fn to_aleo_opcode(
+    operator: BinaryOpKind,
+    lhs_type: Option<&str>,
+    rhs_type: Option<&str>,
+) -> Result<&'static str, CompileError> {
+    let opcode = to_aleo_operator(operator);
+    if is_scalar_multiplication(operator, lhs_type, rhs_type) {
+        return Ok(opcode);
+    }
+    // Shift amounts have a type of their own.
+    let is_shift = matches!(operator, BinaryOpKind::ShiftLeft | BinaryOpKind::ShiftRight);
+    if let (Some(lhs_type), Some(rhs_type), false) = (lhs_type, rhs_type, is_shift) {
+        if lhs_type != rhs_type {
+            return Err(CompileError::TypeMismatch {
+                expected: lhs_type.to_owned(),
+                found: rhs_type.to_owned(),
+            });
+        }
+    }
+    let operand_type = lhs_type.or(rhs_type);
+    let integer_only = matches!(
+        operator,
+        BinaryOpKind::Modulo
+            | BinaryOpKind::And
+            | BinaryOpKind::Or
+            | BinaryOpKind::Xor
+            | BinaryOpKind::ShiftLeft
+            | BinaryOpKind::ShiftRight
+    );
+    let is_supported = match operand_type {
+        Some("field") => !integer_only,
+        // Groups are only multiplied by scalars, handled above.
+        Some("group") => {
+            !integer_only && !matches!(operator, BinaryOpKind::Divide | BinaryOpKind::Multiply)
+        }
+        Some("scalar") => !integer_only && operator != BinaryOpKind::Divide,
+        Some("address") => matches!(operator, BinaryOpKind::Equal | BinaryOpKind::NotEqual),
+        _ => true,
+    };
+    if let (false, Some(operand_type)) = (is_supported, operand_type) {
+        return Err(CompileError::UnsupportedBinaryOperand {
+            opcode,
+            operand_type: operand_type.to_owned(),
+        });
+    }
+    Ok(opcode)
+}
+
+/// The type an unsuffixed literal argument of a built-in method takes, on a
+/// receiver of `receiver_type`.
+fn method_argument_type(opcode: &str, wrap_mode: WrapMode, receiver_type: &str) -> Option<String> {
+    match (opcode, wrap_mode) {
+        // Integers are raised to `u32` powers, like shift amounts.
+        ("pow", _) if is_integer_type(receiver_type) => Some("u32".to_owned()),
+        ("pow", _) => Some(receiver_type.to_owned()),
+        // Wrapping operations take two operands of one type.
+        (_, WrapMode::Wrapping) => Some(receiver_type.to_owned()),
+        _ => None,
+    }
+}
+
+/// Checks that Aleo defines `opcode`, the opcode of the built-in `method`,
+/// on a receiver of `receiver_type` and arguments of `argument_types`.
+fn check_method_operands(
+    method: &'static str,
+    opcode: &str,
+    wrap_mode: WrapMode,
+    receiver_type: &str,
+    argument_types: &[Option<&String>],
+) -> Result<(), CompileError> {
+    if wrap_mode == WrapMode::Wrapping && !is_integer_type(receiver_type) {
+        return Err(CompileError::UnsupportedBinaryOperand {
+            opcode: method,
+            operand_type: receiver_type.to_owned(),
+        });
+    }
+    match opcode {
+        // Only signed integers have an absolute value in Aleo.
+        "abs" if !receiver_type.starts_with('i') || !is_integer_type(receiver_type) => {
+            Err(CompileError::UnsupportedUnaryOperand {
+                opcode: method,
+                operand_type: receiver_type.to_owned(),
+            })
+        }
+        "pow" => {
+            let Some(exponent_type) = argument_types.first().copied().flatten() else {
+                return Ok(());
+            };
+            let is_valid = if is_integer_type(receiver_type) {
+                matches!(exponent_type.as_str(), "u8" | "u16" | "u32")
+            } else if receiver_type == "field" {
+                exponent_type == "field"
+            } else {
+                return Err(CompileError::UnsupportedBinaryOperand {
+                    opcode: method,
+                    operand_type: receiver_type.to_owned(),
+                });
+            };
+            if is_valid {
+                Ok(())
+            } else {
+                Err(CompileError::InvalidExponent {
+                    base_type: receiver_type.to_owned(),
+                    exponent_type: exponent_type.clone(),
+                })
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether `aleo_type` is one of Aleo's signed or unsigned integer types.
+fn is_integer_type(aleo_type: &str) -> bool {
+    aleo_type
+        .strip_prefix(['i', 'u'])
+        .and_then(|bits| bits.parse().ok())
+        .is_some_and(|bits| ALEO_INTEGER_WIDTHS.contains(&bits))
+}
+
+/// Whether the operator multiplies a group element by a scalar, in either
+/// order, which Aleo's `mul` computes as a `group`.
+fn is_scalar_multiplication(
+    operator: BinaryOpKind,
+    lhs_type: Option<&str>,
+    rhs_type: Option<&str>,
+) -> bool {
+    operator == BinaryOpKind::Multiply
+        && matches!(
+            (lhs_type, rhs_type),
+            (Some("group"), Some("scalar")) | (Some("scalar"), Some("group"))
+        )
+}
+
+/// The Aleo opcode asserting `operator` at the top of a `constrain`, if it is
+/// one Aleo can assert without computing its result: `==` and `!=`.
+/// Everywhere else operators compute a value with [`to_aleo_operator`].
+fn to_aleo_assertion(operator: BinaryOpKind) -> Option<Opcode> {
+    match operator {
+        BinaryOpKind::Equal => Some(Opcode::AssertEq),
+        BinaryOpKind::NotEqual => Some(Opcode::AssertNeq),
+        _ => None,
+    }
+}
+
+/// Maps a Noir binary operator to the Aleo opcode computing its value, such
+/// as `is.eq` for `==`, whatever the context of the expression.
+fn to_aleo_operator(operator: BinaryOpKind) -> &'static str {
+    match operator {
+        BinaryOpKind::Add => "add",
+        BinaryOpKind::Subtract => "sub",
+        BinaryOpKind::Multiply => "mul",
+        BinaryOpKind::Divide => "div",
+        BinaryOpKind::Equal => "is.eq",
+        BinaryOpKind::NotEqual => "is.neq",
+        BinaryOpKind::Less => "lt",
+        BinaryOpKind::LessEqual => "lte",
+        BinaryOpKind::Greater => "gt",
+        BinaryOpKind::GreaterEqual => "gte",
+        BinaryOpKind::And => "and",
+        BinaryOpKind::Or => "or",
+        BinaryOpKind::Xor => "xor",
+        BinaryOpKind::ShiftRight => "shr",
+        BinaryOpKind::ShiftLeft => "shl",
+        BinaryOpKind::Modulo => "mod",
+    }
+}
+
+fn push_new_line(string: &mut String) {
+    string.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Compiles the package in `program_dir`, returning errors without their
+    /// location so that tests can match them against a variant.
+    fn compile_program(program_dir: &Path) -> Result<String, CompileError> {
+        let package = resolve(program_dir).map_err(CompileError::without_location)?;
+        compile(&package, &CompileOptions::default())
+            .map(|program| program.aleo_program)
+            .map_err(CompileError::without_location)
+    }
+
+    #[test]
+    fn test_compile_noir_hello_world_to_aleo_instructions() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let package = resolve(Path::new("tests/hello_world")).unwrap();
+        let compiled_program = compile(&package, &CompileOptions::default()).unwrap();
+        let output_config = OutputConfig {
+            output_dir: output_dir.path().to_owned(),
+        };
+        let path = write(&compiled_program, &output_config).unwrap();
+        assert_eq!(path, output_dir.path().join("hello_world.aleo"));
+        let aleo_program = fs::read_to_string(path).unwrap();
+        assert_eq!(aleo_program, compiled_program.aleo_program);
+    }
+
+    #[test]
+    fn test_program_named_in_manifest() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            output_dir: output_dir.path().to_owned(),
+        };
+        let compiled_program = compile_to_aleo_instructions(
+            Path::new("tests/named_package"),
+            &CompileOptions::default(),
+            Some(&output_config),
+        )
+        .unwrap();
+        assert!(compiled_program
+            .aleo_program
+            .starts_with("program adder.aleo;\n"));
+        assert!(output_dir.path().join("adder.aleo").is_file());
+    }
+
+    #[test]
+    fn test_program_name_override() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let options = CompileOptions {
+            program_name: Some("adder".to_owned()),
+            ..CompileOptions::default()
+        };
+        let output_config = OutputConfig {
+            output_dir: output_dir.path().to_owned(),
+        };
+        let compiled_program =
+            compile_to_aleo_instructions(Path::new("tests/add"), &options, Some(&output_config))
+                .unwrap();
+        assert!(compiled_program
+            .aleo_program
+            .starts_with("program adder.aleo;\n"));
+        let written = fs::read_to_string(output_dir.path().join("adder.aleo")).unwrap();
+        assert_eq!(written, compiled_program.aleo_program);
+    }
+
+    fn parse_build_args(args: &[&str]) -> BuildArgs {
+        let cli = Cli::parse_from(["noir_to_aleo_instructions", "build"].iter().chain(args));
+        match cli.command {
+            Command::Build(args) => args,
+            command => panic!("expected a build command, got {command:?}"),
+        }
+    }
+
+    #[test]
+    fn test_cli_arguments() {
+        let args = parse_build_args(&[
+            "tests/add",
+            "--output-dir",
+            "out",
+            "--program-name",
+            "adder",
+            "--print",
+        ]);
+        assert_eq!(args.program_dir, Path::new("tests/add"));
+        assert_eq!(args.output_dir, Path::new("out"));
+        assert_eq!(args.program_name.as_deref(), Some("adder"));
+        assert!(args.print);
+        assert!(!args.verbose);
+        assert!(parse_build_args(&["--verbose"]).verbose);
+
+        let args = parse_build_args(&[]);
+        assert_eq!(args.program_dir, Path::new("."));
+        assert_eq!(args.output_dir, Path::new(ALEO_BUILD_DIR));
+        assert_eq!(args.program_name, None);
+        assert!(!args.print);
+        assert!(!args.verbose);
+        assert!(args.emit.is_empty());
+
+        let args = parse_build_args(&["--emit", "test-vectors"]);
+        assert_eq!(args.emit, vec![Emit::TestVectors]);
+
+        let args = parse_build_args(&["--emit-ir"]);
+        assert!(args.emit_ir);
+        assert!(!args.optimize);
+        assert!(parse_build_args(&["--optimize"]).optimize);
+        assert!(Cli::try_parse_from([
+            "noir_to_aleo_instructions",
+            "build",
+            "--print",
+            "--emit-ir"
+        ])
+        .is_err());
+
+        let cli = Cli::parse_from(["noir_to_aleo_instructions", "check", "tests/add", "-q"]);
+        assert!(cli.quiet);
+        assert!(
+            matches!(cli.command, Command::Check(args) if args.program_dir == Path::new("tests/add"))
+        );
+    }
+
+    #[test]
+    fn test_nargo_subcommand_args() {
+        let current_dir = fs::canonicalize("tests/add/src").unwrap();
+        let package_root = current_dir.parent().unwrap().to_owned();
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+
+        let rewritten =
+            nargo_subcommand_args(args(&["nargo-aleo", "aleo", "build", "-q"]), &current_dir)
+                .unwrap();
+        let mut expected = args(&["nargo-aleo", "build"]);
+        expected.push(package_root.clone().into());
+        expected.extend(args(&["-q", "--output-dir"]));
+        expected.push(package_root.join(ALEO_BUILD_DIR).into());
+        assert_eq!(rewritten, expected);
+
+        let rewritten = nargo_subcommand_args(
+            args(&["nargo-aleo", "aleo", "check", "--program-dir=.."]),
+            &current_dir,
+        )
+        .unwrap();
+        let mut expected = args(&["nargo-aleo", "check"]);
+        expected.push(current_dir.join("..").into());
+        assert_eq!(rewritten, expected);
+
+        let rewritten =
+            nargo_subcommand_args(args(&["nargo-aleo", "aleo", "--help"]), &current_dir).unwrap();
+        assert_eq!(rewritten, args(&["nargo-aleo", "--help"]));
+    }
+
+    #[test]
+    fn test_emit_test_vectors() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let options = CompileOptions {
+            emit_test_vectors: true,
+            ..CompileOptions::default()
+        };
+        let output_config = OutputConfig {
+            output_dir: output_dir.path().to_owned(),
+        };
+        let compiled_program = compile_to_aleo_instructions(
+            Path::new("tests/test_vectors"),
+            &options,
+            Some(&output_config),
+        )
+        .unwrap();
+        assert!(!compiled_program.aleo_program.contains("function test_"));
+        let tests_dir = output_dir.path().join("tests");
+        assert_eq!(fs::read_dir(&tests_dir).unwrap().count(), 2);
+        assert_eq!(
+            fs::read_to_string(tests_dir.join("test_add.json")).unwrap(),
+            "{\"function\": \"main\", \"inputs\": [\"1u32\", \"2u32\"], \"outputs\": [\"3u32\"]}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(tests_dir.join("test_add_zero.json")).unwrap(),
+            "{\"function\": \"main\", \"inputs\": [\"5u32\", \"0u32\"], \"outputs\": [\"5u32\"]}\n"
+        );
+        assert_eq!(
+            compiled_program.skipped_tests,
+            vec![SkippedTest {
+                name: "test_add_variables".to_owned(),
+                reason: "it is not a single call followed by an assertion on its result",
+            }]
+        );
+    }
+
+    #[test]
+    fn test_profile_records_every_phase() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            output_dir: output_dir.path().to_owned(),
+        };
+        let compiled_program = compile_to_aleo_instructions(
+            Path::new("tests/function_calls"),
+            &CompileOptions::default(),
+            Some(&output_config),
+        )
+        .unwrap();
+        let names: Vec<&str> = compiled_program
+            .profile
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "resolve",
+                "compile",
+                "write",
+                "function main",
+                "function ping",
+                "function pong",
+                "fold constants",
+                "eliminate dead code"
+            ]
+        );
+        let json = compiled_program.profile.to_json();
+        for name in names {
+            assert!(json.contains(&format!("\"name\": \"{name}\"")));
+        }
+        let counts_allocations = cfg!(feature = "alloc-profile");
+        assert!(compiled_program
+            .profile
+            .entries
+            .iter()
+            .all(|entry| entry.peak_bytes.is_some() == counts_allocations));
+        assert_eq!(json.contains("\"peak_bytes\": "), counts_allocations);
+    }
+
+    #[test]
+    fn test_invalid_intrinsics_are_located_in_their_file() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let program_dir = output_dir.path().join("intrinsics");
+        fs::create_dir_all(program_dir.join("src")).unwrap();
+        fs::copy("tests/add/Nargo.toml", program_dir.join("Nargo.toml")).unwrap();
+        fs::copy("tests/add/src/main.nr", program_dir.join("src/main.nr")).unwrap();
+        let intrinsics_file = program_dir.join(not_nargo::toml::INTRINSICS_FILE);
+        let error_with = |entry: &str| {
+            fs::write(&intrinsics_file, format!("[\"mylib::hash\"]\n{entry}")).unwrap();
+            match resolve(&program_dir) {
+                Ok(_) => panic!("{entry:?} was accepted"),
+                Err(error) => error.to_string().replace(
+                    &intrinsics_file.display().to_string(),
+                    "aleo_intrinsics.toml",
+                ),
+            }
+        };
+        assert_eq!(
+            error_with("opcode = \"hash.md5\"\narguments = [\"field\"]\nresult = \"field\"\n"),
+            "aleo_intrinsics.toml:2:10: invalid intrinsic `mylib::hash`: \
+             `hash.md5` is not a hash, commitment or signature opcode"
+        );
+        assert_eq!(
+            error_with(
+                "opcode = \"hash.psd4\"\narguments = [\"field\", \"field\"]\nresult = \"field\"\n"
+            ),
+            "aleo_intrinsics.toml:3:13: invalid intrinsic `mylib::hash`: \
+             `hash.psd4` takes 1 operands but 2 arguments are listed"
+        );
+    }
+
+    #[test]
+    fn test_resolve_reads_package_metadata() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let program_dir = output_dir.path().join("described");
+        fs::create_dir_all(program_dir.join("src")).unwrap();
+        fs::write(
+            program_dir.join("Nargo.toml"),
+            "[package]\ndescription = \"Adds two numbers.\"\n\n[dependencies]\n",
+        )
+        .unwrap();
+        fs::copy("tests/add/src/main.nr", program_dir.join("src/main.nr")).unwrap();
+        let package = resolve(&program_dir).unwrap();
+        assert_eq!(package.description.as_deref(), Some("Adds two numbers."));
+        assert_eq!(package.name, "described");
+        assert_eq!(package.noir_ast.functions.len(), 1);
+    }
+
+    #[test]
+    fn test_binary_operators() {
+        let aleo_program = compile_program(Path::new("tests/binary_operators")).unwrap();
+        let expected = "program binary_operators.aleo;
+
+transition add:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    add r0 r1 into r2;
+    output r2 as u32.private;
+
+transition subtract:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    sub r0 r1 into r2;
+    output r2 as u32.private;
+
+transition multiply:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    mul r0 r1 into r2;
+    output r2 as u32.private;
+
+transition divide:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    div r0 r1 into r2;
+    output r2 as u32.private;
+
+transition modulo:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    mod r0 r1 into r2;
+    output r2 as u32.private;
+
+transition equal:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    is.eq r0 r1 into r2;
+    output r2 as boolean.private;
+
+transition not_equal:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    is.neq r0 r1 into r2;
+    output r2 as boolean.private;
+
+transition less:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    lt r0 r1 into r2;
+    output r2 as boolean.private;
+
+transition less_equal:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    lte r0 r1 into r2;
+    output r2 as boolean.private;
+
+transition greater:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    gt r0 r1 into r2;
+    output r2 as boolean.private;
+
+transition greater_equal:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    gte r0 r1 into r2;
+    output r2 as boolean.private;
+
+transition and:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    and r0 r1 into r2;
+    output r2 as u32.private;
+
+transition or:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    or r0 r1 into r2;
+    output r2 as u32.private;
+
+transition xor:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    xor r0 r1 into r2;
+    output r2 as u32.private;
+
+transition shift_right:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    shr r0 r1 into r2;
+    output r2 as u32.private;
+
+transition shift_left:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    shl r0 r1 into r2;
+    output r2 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_struct_constructor_output() {
+        let aleo_program = compile_program(Path::new("tests/struct_output")).unwrap();
+        let expected = "program struct_output.aleo;
+
+struct Point:
+    x as field;
+    y as field;
+
+transition main:
+    input r0 as field.private;
+    input r1 as field.private;
+    cast r0 r1 into r2 as Point;
+    output r2 as Point.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_unsigned_integer_widths() {
+        let aleo_program = compile_program(Path::new("tests/unsigned_widths")).unwrap();
+        let expected = "program unsigned_widths.aleo;
+
+transition add_u8:
+    input r0 as u8.private;
+    input r1 as u8.private;
+    add r0 r1 into r2;
+    output r2 as u8.private;
+
+transition sub_u16:
+    input r0 as u16.private;
+    input r1 as u16.private;
+    sub r0 r1 into r2;
+    output r2 as u16.private;
+
+transition mul_u32:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    mul r0 r1 into r2;
+    output r2 as u32.private;
+
+transition div_u64:
+    input r0 as u64.private;
+    input r1 as u64.private;
+    div r0 r1 into r2;
+    output r2 as u64.private;
+
+transition add_u128:
+    input r0 as u128.private;
+    input r1 as u128.private;
+    add r0 r1 into r2;
+    add r2 1u128 into r3;
+    output r3 as u128.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_signed_integer_widths() {
+        let aleo_program = compile_program(Path::new("tests/signed_widths")).unwrap();
+        let expected = "program signed_widths.aleo;
+
+transition sub_i8:
+    input r0 as i8.private;
+    input r1 as i8.private;
+    sub r0 r1 into r2;
+    output r2 as i8.private;
+
+transition mul_i64:
+    input r0 as i64.private;
+    input r1 as i64.private;
+    mul r0 r1 into r2;
+    add r2 1i64 into r3;
+    output r3 as i64.private;
+
+transition widen:
+    input r0 as i8.private;
+    cast r0 into r1 as i64;
+    output r1 as i64.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_unsupported_signed_integer_width_is_rejected() {
+        let error = to_aleo_type(&UnresolvedType::Integer(Signedness::Signed, 24)).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::UnsupportedIntegerWidth("i24".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_unsupported_integer_width_is_rejected() {
+        let error = compile_program(Path::new("tests/unsupported_integer_width")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::UnsupportedIntegerWidth("u24".to_owned())
+        );
+        assert_eq!(
+            error.to_string(),
+            "Aleo has no `u24` type, integers must be 8, 16, 32, 64 or 128 bits wide"
+        );
+    }
+
+    #[test]
+    fn test_widening_cast() {
+        let aleo_program = compile_program(Path::new("tests/widening_cast")).unwrap();
+        let expected = "program widening_cast.aleo;
+
+transition main:
+    input r0 as u32.private;
+    cast r0 into r1 as u64;
+    output r1 as u64.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_narrowing_cast() {
+        let aleo_program = compile_program(Path::new("tests/narrowing_cast")).unwrap();
+        let expected = "program narrowing_cast.aleo;
+
+transition main:
+    input r0 as u64.private;
+    cast r0 into r1 as u8;
+    output r1 as u8.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_cast_to_field_then_add() {
+        let aleo_program = compile_program(Path::new("tests/cast_to_field")).unwrap();
+        let expected = "program cast_to_field.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as field.private;
+    cast r0 into r2 as field;
+    add r2 r1 into r3;
+    output r3 as field.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_field_to_bool_cast_is_rejected() {
+        let error = compile_program(Path::new("tests/field_to_bool_cast")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::UnsupportedCast {
+                from: "field".to_owned(),
+                to: "boolean".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_struct_definition() {
+        let noir_ast =
+            noir_parser::parse_program("struct Point {\n    x: u32,\n    y: u32,\n}\n").unwrap();
+        assert_eq!(
+            to_aleo_struct_definition(&noir_ast.types[0])
+                .unwrap()
+                .to_string(),
+            "struct Point:\n    x as u32;\n    y as u32;\n"
+        );
+    }
+
+    #[test]
+    fn test_record_definition() {
+        let aleo_program = compile_program(Path::new("tests/record_definition")).unwrap();
+        let expected = "program record_definition.aleo;
+
+record Token:
+    owner as address.private;
+    gates as u64.private;
+    amount as u64.private;
+
+transition main:
+    input r0 as u64.private;
+    output r0 as u64.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    /// Compiles `types` followed by a function, returning the error it
+    /// fails with.
+    fn layout_error(types: &str) -> CompileError {
+        let source = format!("{types}\nfn main(x : u32) -> u32 {{\n    x\n}}\n");
+        compile_source(&source, "main", &CompileOptions::default())
+            .unwrap_err()
+            .without_location()
+    }
+
+    #[test]
+    fn test_recursive_structs_are_rejected() {
+        let error = layout_error("struct Node {\n    next: Node,\n}\n");
+        assert_eq!(
+            error.to_string(),
+            "struct `Node` contains itself through Node -> Node, which Aleo cannot lay out"
+        );
+
+        let error = layout_error(
+            "struct Id {\n    number: u32,\n}\nstruct A {\n    id: Id,\n    b: [B; 2],\n}\nstruct B {\n    c: C,\n}\nstruct C {\n    a: A,\n}\n",
+        );
+        assert_eq!(
+            error,
+            CompileError::RecursiveStruct(vec![
+                "A".to_owned(),
+                "B".to_owned(),
+                "C".to_owned(),
+                "A".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_records_cannot_be_members() {
+        let token = "#[record]\nstruct Token {\n    amount: u64,\n}\n";
+        for (types, struct_name, field, reason) in [
+            (
+                "struct Wallet {\n    token: Token,\n}\n",
+                "Wallet",
+                "token",
+                "structs cannot contain records",
+            ),
+            (
+                "struct Wallet {\n    tokens: [Token; 2],\n}\n",
+                "Wallet",
+                "tokens",
+                "structs cannot contain records",
+            ),
+            (
+                "#[record]\nstruct Bundle {\n    token: Token,\n}\n",
+                "Bundle",
+                "token",
+                "records cannot contain other records",
+            ),
+        ] {
+            assert_eq!(
+                layout_error(&format!("{token}{types}")),
+                CompileError::InvalidStructMember {
+                    struct_name: struct_name.to_owned(),
+                    field: field.to_owned(),
+                    reason,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_record_with_invalid_owner() {
+        let error = compile_program(Path::new("tests/record_invalid_owner")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::InvalidRecordField {
+                record: "Token".to_owned(),
+                field: "owner".to_owned(),
+                expected: "address",
+                found: "field".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_constrained_inputs_in_return_value() {
+        let aleo_program = compile_program(Path::new("tests/constrained_subtraction")).unwrap();
+        let expected = "program constrained_subtraction.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    lte r0 r1 into r2;
+    assert.eq r2 true;
+    sub r1 r0 into r3;
+    output r3 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_reserved_function_name() {
+        let error = compile_program(Path::new("tests/reserved_function_name")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::InvalidIdentifier {
+                kind: "function",
+                name: "output".to_owned(),
+                reason: "it is a reserved word in Aleo",
+            }
+        );
+    }
+
+    #[test]
+    fn test_long_function_name() {
+        let error = compile_program(Path::new("tests/long_function_name")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::InvalidIdentifier {
+                kind: "function",
+                name: "double_the_value_of_the_given_argument_x".to_owned(),
+                reason: "it is longer than 31 bytes",
+            }
+        );
+    }
+
+    #[test]
+    fn test_mapping_counter() {
+        let aleo_program = compile_program(Path::new("tests/mapping_counter")).unwrap();
+        let expected = "program mapping_counter.aleo;
+
+mapping counters:
+    key as u32.public;
+    value as u64.public;
+
+transition main:
+    input r0 as u32.public;
+    finalize r0;
+finalize main:
+    input r0 as u32.public;
+    get.or_use counters[r0] 0u64 into r1;
+    add r1 1u64 into r2;
+    set r2 into counters[r0];
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_mapping_operations_are_rejected_outside_finalize() {
+        let error = compile_program(Path::new("tests/mapping_in_transition")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::InvalidMapping {
+                name: "counters".to_owned(),
+                reason: "mapping operations are only allowed in finalize",
+            }
+        );
+    }
+
+    #[test]
+    fn test_callable_kinds() {
+        let aleo_program = compile_program(Path::new("tests/callable_kinds")).unwrap();
+        let expected = "program callable_kinds.aleo;
+
+transition main:
+    input r0 as u32.private;
+    call double r0 into r1;
+    call increment r0 into r2;
+    call square r2 into r3;
+    add r1 r3 into r4;
+    output r4 as u32.private;
+
+function double:
+    input r0 as u32.private;
+    add r0 r0 into r1;
+    output r1 as u32.private;
+
+closure square:
+    input r0 as u32;
+    mul r0 r0 into r1;
+    output r1 as u32;
+
+transition increment:
+    input r0 as u32.private;
+    add r0 1u32 into r1;
+    output r1 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_finalize_counter() {
+        let aleo_program = compile_program(Path::new("tests/finalize_counter")).unwrap();
+        let expected = "program finalize_counter.aleo;
+
+mapping counters:
+    key as u32.public;
+    value as u64.public;
+
+transition main:
+    input r0 as u32.public;
+    finalize r0;
+finalize main:
+    input r0 as u32.public;
+    get.or_use counters[r0] 0u64 into r1;
+    add r1 1u64 into r2;
+    set r2 into counters[r0];
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_mapping_without_key_type() {
+        let noir_ast =
+            noir_parser::parse_program("#[mapping]\nglobal counters: u64 = 0;\n").unwrap();
+        assert_eq!(
+            collect_mappings(&noir_ast).unwrap_err(),
+            CompileError::InvalidMapping {
+                name: "counters".to_owned(),
+                reason: "the key type must be given as `#[mapping(KeyType)]`",
+            }
+        );
+    }
+
+    #[test]
+    fn test_struct_constructor() {
+        let aleo_program = compile_program(Path::new("tests/struct_constructor")).unwrap();
+        let expected = "program struct_constructor.aleo;
+
+struct Account:
+    id as u32;
+    balance as u32;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.public;
+    mul r1 r1 into r2;
+    cast r0 r2 into r3 as Account;
+    output r3 as Account.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_struct_constructor_missing_field() {
+        let error =
+            compile_program(Path::new("tests/struct_constructor_missing_field")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::MissingStructField {
+                struct_name: "Account".to_owned(),
+                field: "balance".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_struct_constructor_extra_field() {
+        let error = compile_program(Path::new("tests/struct_constructor_extra_field")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::UnknownStructField {
+                struct_name: "Account".to_owned(),
+                field: "nonce".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_member_access() {
+        let aleo_program = compile_program(Path::new("tests/member_access")).unwrap();
+        let expected = "program member_access.aleo;
+
+struct Point:
+    x as u32;
+    y as u32;
+
+transition main:
+    input r0 as Point.private;
+    mul r0.x r0.y into r1;
+    output r1 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_unknown_member_is_rejected() {
+        let error = compile_program(Path::new("tests/unknown_member")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::UnknownStructField {
+                struct_name: "Point".to_owned(),
+                field: "z".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_member_access_on_call_result() {
+        let aleo_program =
+            compile_program(Path::new("tests/member_access_on_call_result")).unwrap();
+        let expected = "program member_access_on_call_result.aleo;
+
+struct Point:
+    x as u32;
+    y as u32;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    call make_point r0 r1 into r2;
+    add r2.x r1 into r3;
+    output r3 as u32.private;
+
+function make_point:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    cast r0 r1 into r2 as Point;
+    output r2 as Point.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_member_access_on_cast_result() {
+        let aleo_program =
+            compile_program(Path::new("tests/member_access_on_cast_result")).unwrap();
+        let expected = "program member_access_on_cast_result.aleo;
+
+struct Point:
+    x as u32;
+    y as u32;
+
+struct Pair:
+    first as u32;
+    second as u32;
+
+transition main:
+    input r0 as Point.private;
+    cast r0.x r0.y into r1 as Pair;
+    add r1.second r0.x into r2;
+    output r2 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_tuple_member_access() {
+        let aleo_program = compile_program(Path::new("tests/tuple_member_access")).unwrap();
+        let expected = "program tuple_member_access.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    sub r1 r0 into r2;
+    output r2 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_unit_return_has_no_output() {
+        let aleo_program = compile_program(Path::new("tests/unit_return")).unwrap();
+        let expected = "program unit_return.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.public;
+    assert.eq r0 r1;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_constrain_only_function_has_no_output() {
+        let aleo_program = compile_program(Path::new("tests/constrain_only")).unwrap();
+        let expected = "program constrain_only.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    assert.neq r0 r1;
+    lt r0 r1 into r2;
+    assert.eq r2 true;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_trailing_constrain_is_not_a_return_value() {
+        let error = compile_program(Path::new("tests/trailing_constrain")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::TypeMismatch {
+                expected: "u32".to_owned(),
+                found: "()".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_void_function_call() {
+        let aleo_program = compile_program(Path::new("tests/void_function_call")).unwrap();
+        let expected = "program void_function_call.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    call check r0 r1;
+    call check r1 r0;
+
+function check:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    assert.neq r0 r1;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_empty_body() {
+        let aleo_program = compile_program(Path::new("tests/empty_body")).unwrap();
+        let expected = "program empty_body.aleo;
+
+transition noop:
+    input r0 as field.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_empty_body_with_return_type_is_rejected() {
+        let error = compile_program(Path::new("tests/empty_body_with_return_type")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::MissingReturnValue {
+                function: "main".to_owned(),
+                return_type: "field".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_empty_registry_is_reported() {
+        let error = compile_program(Path::new("tests/missing_return_value")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::TypeMismatch {
+                expected: "u32".to_owned(),
+                found: "()".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_tuple_parameters() {
+        let aleo_program = compile_program(Path::new("tests/tuple_parameters")).unwrap();
+        let expected = "program tuple_parameters.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    add r0 r1 into r2;
+    output r2 as u32.private;
+
+transition identity:
+    input r0 as u32.private;
+    input r1 as field.private;
+    output r0 as u32.private;
+    output r1 as field.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_tuple_return() {
+        let aleo_program = compile_program(Path::new("tests/tuple_return")).unwrap();
+        let expected = "program tuple_return.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    add r0 r1 into r2;
+    sub r0 r1 into r3;
+    output r2 as u32.public;
+    output r3 as u32.public;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_function_calls() {
+        let aleo_program = compile_program(Path::new("tests/function_calls")).unwrap();
+        let expected = "program function_calls.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    call ping r0 r1 into r2;
+    output r2 as u32.private;
+
+function ping:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    add r0 r1 into r2;
+    call pong r2 r1 into r3;
+    output r3 as u32.private;
+
+function pong:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    sub r0 r1 into r2;
+    call ping r2 r1 into r3;
+    output r3 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_undeclared_types_are_rejected() {
+        let compile = |source: &str| {
+            compile_source(source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(|error| error.to_string())
+        };
+        assert_eq!(
+            compile("fn main(x : Foo) -> pub u32 {\n    1\n}\n").unwrap_err(),
+            "main.nr:1:13: cannot find struct `Foo` in this program"
+        );
+        assert_eq!(
+            compile("fn main(x : u32) -> pub [Foo; 2] {\n    x\n}\n").unwrap_err(),
+            "main.nr:1:26: cannot find struct `Foo` in this program"
+        );
+        assert_eq!(
+            compile("struct Pair {\n    x : u32,\n    y : Bar,\n}\n\nfn main(x : u32) -> pub u32 {\n    x\n}\n")
+                .unwrap_err(),
+            "main.nr:3:9: cannot find struct `Bar` in this program"
+        );
+        assert!(compile("fn main(x : Address) -> pub Address {\n    x\n}\n").is_ok());
+    }
+
+    #[test]
+    fn test_external_call_is_rejected() {
+        let error = compile_program(Path::new("tests/external_call")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::UnsupportedExternalCall("token::transfer".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_bhp_hashes_compile_to_hash_instructions() {
+        let compile = |body: &str| {
+            let source = format!("fn main(x : u32) -> pub Field {{\n    {body}\n}}\n");
+            compile_source(&source, "main", &CompileOptions::default())
+                .unwrap()
+                .aleo_program
+        };
+        assert!(compile("std::hash::bhp256(x)")
+            .ends_with("    hash.bhp256 r0 into r1 as field;\n    output r1 as field.public;\n"));
+        assert!(compile("std::hash::bhp1024(x)")
+            .ends_with("    hash.bhp1024 r0 into r1 as field;\n    output r1 as field.public;\n"));
+    }
+
+    #[test]
+    fn test_commitment_randomness_must_be_a_scalar() {
+        let source = "fn main(x : Field, r : Field) -> pub Field {\n    std::hash::bhp256_commitment(x, r)\n}\n";
+        let error = compile_source(source, "main", &CompileOptions::default()).unwrap_err();
+        assert_eq!(
+            error.without_location(),
+            CompileError::InvalidIntrinsicArgument {
+                intrinsic: "std::hash::bhp256_commitment".to_owned(),
+                found: "field".to_owned(),
+                accepted: vec!["scalar".to_owned()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_intrinsic_arguments_are_checked() {
+        let compile = |body: &str| {
+            let source = format!("fn main(x : Field) -> pub Field {{\n    {body}\n}}\n");
+            compile_source(&source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(CompileError::without_location)
+        };
+        assert!(compile("std::hash::poseidon(x)")
+            .unwrap()
+            .ends_with("    hash.psd2 r0 into r1 as field;\n    output r1 as field.public;\n"));
+        assert_eq!(
+            compile("std::hash::pedersen(x)").unwrap_err(),
+            CompileError::InvalidIntrinsicArgument {
+                intrinsic: "std::hash::pedersen".to_owned(),
+                found: "field".to_owned(),
+                accepted: PED64_INPUT_TYPES
+                    .iter()
+                    .map(|typ| (*typ).to_owned())
+                    .collect(),
+            }
+        );
+        assert_eq!(
+            compile("std::hash::blake2s(x)").unwrap_err(),
+            CompileError::NoAleoEquivalent {
+                path: "std::hash::blake2s".to_owned(),
+                reason: None,
+            }
+        );
+        let error = compile("std::hash::poseidon2(x)").unwrap_err();
+        assert!(matches!(
+            &error,
+            CompileError::NoAleoEquivalent { path, reason: Some(_) } if path == "std::hash::poseidon2"
+        ));
+        assert!(error
+            .to_string()
+            .contains("Aleo's `hash.psd2` computes Poseidon, not Poseidon2."));
+        assert_eq!(
+            compile("std::hash::poseidon(x, x)").unwrap_err(),
+            CompileError::ArgumentCountMismatch {
+                name: "std::hash::poseidon".to_owned(),
+                expected: 1,
+                found: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_tuple_destructuring() {
+        let aleo_program = compile_program(Path::new("tests/tuple_destructuring")).unwrap();
+        let expected = "program tuple_destructuring.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    add r0 r1 into r2;
+    sub r0 r1 into r3;
+    mul r2 r3 into r4;
+    output r4 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_tuple_destructuring_arity_mismatch() {
+        let error = compile_program(Path::new("tests/tuple_destructuring_arity")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::TupleArityMismatch {
+                expected: 2,
+                found: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_array_input() {
+        let aleo_program = compile_program(Path::new("tests/array_input")).unwrap();
+        let expected = "program array_input.aleo;
+
+transition main:
+    input r0 as [u32; 4u32].private;
+    output r0[0u32] as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_array_literal() {
+        let aleo_program = compile_program(Path::new("tests/array_literal")).unwrap();
+        let expected = "program array_literal.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    input r2 as u32.private;
+    input r3 as u32.private;
+    cast r0 r1 r2 r3 into r4 as [u32; 4u32];
+    output r4 as [u32; 4u32].private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_public_array_return() {
+        let package = resolve(Path::new("tests/public_array_return")).unwrap();
+        let compiled_program = compile(&package, &CompileOptions::default()).unwrap();
+        let expected = "program public_array_return.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    cast r0 r1 into r2 as [u32; 2u32];
+    output r2[0u32] as u32.public;
+    output r2[1u32] as u32.public;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+        assert_eq!(
+            compiled_program.abi[0].return_annotation(),
+            "distinct pub [u32; 2]"
+        );
+        assert_eq!(
+            abi_to_json(&compiled_program.abi),
+            "{\"functions\": [{\"name\": \"main\", \"parameters\": [{\"name\": \"a\", \"visibility\": \"private\"}, {\"name\": \"b\", \"visibility\": \"private\"}], \"return\": \"distinct pub [u32; 2]\", \"distinct\": true}]}\n"
+        );
+    }
+
+    #[test]
+    fn test_default_visibility_from_the_package() {
+        let package = resolve(Path::new("tests/default_visibility")).unwrap();
+        let compiled_program = compile(&package, &CompileOptions::default()).unwrap();
+        let expected = "program default_visibility.aleo;
+
+transition main:
+    input r0 as u32.public;
+    input r1 as u32.private;
+    input r2 as u32.public;
+    add r0 r1 into r3;
+    add r3 r2 into r4;
+    output r4 as u32.private;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+        assert_eq!(
+            abi_to_json(&compiled_program.abi),
+            "{\"functions\": [{\"name\": \"main\", \"parameters\": [{\"name\": \"x\", \"visibility\": \"public\"}, {\"name\": \"secret\", \"visibility\": \"private\"}, {\"name\": \"y\", \"visibility\": \"public\"}], \"return\": \"u32\", \"distinct\": false}]}\n"
+        );
+    }
+
+    #[test]
+    fn test_default_visibility_option_beats_the_package() {
+        let options = CompileOptions {
+            default_visibility: Some(Visibility::Private),
+            ..CompileOptions::default()
+        };
+        let package = resolve(Path::new("tests/default_visibility")).unwrap();
+        let compiled_program = compile(&package, &options).unwrap();
+        assert!(compiled_program
+            .aleo_program
+            .contains("    input r0 as u32.private;\n    input r1 as u32.private;\n    input r2 as u32.public;\n"));
+
+        let options = CompileOptions {
+            default_visibility: Some(Visibility::Public),
+            ..CompileOptions::default()
+        };
+        let package = resolve(Path::new("tests/add")).unwrap();
+        let compiled_program = compile(&package, &options).unwrap();
+        assert!(compiled_program
+            .aleo_program
+            .contains("    input r0 as u32.public;\n    input r1 as u32.public;\n"));
+        assert_eq!(
+            parse_build_args(&["--default-visibility", "public"]).default_visibility,
+            Some(Visibility::Public)
+        );
+    }
+
+    #[test]
+    fn test_long_struct_casts_are_split() {
+        let package = resolve(Path::new("tests/long_struct_cast")).unwrap();
+        let cast = "    cast r0.alice r0.bob r0.carol r0.dave r0.erin r0.frank r0.grace r0.heidi r0.ivan r0.judy 0u64 r2 into r3 as Allocation;\n";
+        let compiled_program = compile(&package, &CompileOptions::default()).unwrap();
+        assert!(compiled_program.aleo_program.contains(cast));
+
+        let options = CompileOptions {
+            max_line_width: Some(80),
+            ..CompileOptions::default()
+        };
+        let compiled_program = compile(&package, &options).unwrap();
+        let body = compiled_program
+            .aleo_program
+            .split_once("    add r1 r0.mallory into r2;\n")
+            .unwrap()
+            .1;
+        let expected = "    cast r0.alice into r3 as u64;
+    cast r0.carol into r4 as u64;
+    cast r0.frank into r5 as u64;
+    cast r0.grace into r6 as u64;
+    cast r0.heidi into r7 as u64;
+    cast r0.dave into r8 as u64;
+    cast r0.erin into r9 as u64;
+    cast r0.ivan into r10 as u64;
+    cast r3 r0.bob r4 r8 r9 r5 r6 r7 r10 r0.judy 0u64 r2 into r11 as Allocation;
+    output r11 as Allocation.public;
+";
+        assert_eq!(body, expected);
+        assert!(body.lines().all(|line| line.len() <= 80));
+    }
+
+    #[test]
+    fn test_types_only_program_needs_the_interface_dialect() {
+        let error = compile_program(Path::new("tests/types_only")).unwrap_err();
+        assert_eq!(error, CompileError::NoFunctions);
+
+        let package = resolve(Path::new("tests/types_only_interface")).unwrap();
+        let compiled_program = compile(&package, &CompileOptions::default()).unwrap();
+        let expected = "program types_only_interface.aleo;
+
+struct Point:
+    x as field;
+    y as field;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+        assert_eq!(
+            compiled_program.warnings,
+            ["the program defines no functions, only its types"]
+        );
+
+        let options = CompileOptions {
+            dialect: Some(Dialect::SnarkVm),
+            ..CompileOptions::default()
+        };
+        assert_eq!(
+            compile(&package, &options).unwrap_err(),
+            CompileError::NoFunctions
+        );
+    }
+
+    #[test]
+    fn test_private_attribute_must_name_a_parameter() {
+        let error = compile_program(Path::new("tests/private_unknown_parameter")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::InvalidVisibilityOverride {
+                function: "main".to_owned(),
+                parameter: "secret".to_owned(),
+                reason: "it is not one of its parameters",
+            }
+        );
+    }
+
+    #[test]
+    fn test_array_literal_element_type_mismatch() {
+        let error = compile_program(Path::new("tests/array_literal_type_mismatch")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::ArrayElementTypeMismatch {
+                expected: "u32".to_owned(),
+                found: "u8".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_field_negation() {
+        let aleo_program = compile_program(Path::new("tests/field_negation")).unwrap();
+        let expected = "program field_negation.aleo;
+
+transition main:
+    input r0 as field.private;
+    neg r0 into r1;
+    output r1 as field.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_boolean_not() {
+        let aleo_program = compile_program(Path::new("tests/boolean_not")).unwrap();
+        let expected = "program boolean_not.aleo;
+
+transition main:
+    input r0 as boolean.private;
+    not r0 into r1;
+    output r1 as boolean.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_boolean_negation_is_rejected() {
+        let error = compile_program(Path::new("tests/boolean_negation")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::UnsupportedUnaryOperand {
+                opcode: "neg",
+                operand_type: "boolean".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unsigned_negation_is_rejected() {
+        let error = compile_program(Path::new("tests/unsigned_negation")).unwrap_err();
+        assert_eq!(error, CompileError::UnsignedNegation("u32".to_owned()));
+    }
+
+    #[test]
+    fn test_constant_array_index() {
+        let aleo_program = compile_program(Path::new("tests/constant_array_index")).unwrap();
+        let expected = "program constant_array_index.aleo;
+
+transition main:
+    input r0 as [u32; 4u32].private;
+    add r0[1u32] r0[3u32] into r1;
+    output r1 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_out_of_bounds_array_index() {
+        let error = compile_program(Path::new("tests/out_of_bounds_array_index")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::IndexOutOfBounds {
+                index: 4,
+                length: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_dynamic_array_index_is_unsupported() {
+        let error = compile_program(Path::new("tests/dynamic_array_index")).unwrap_err();
+        assert_eq!(error, CompileError::DynamicArrayIndex);
+    }
+
+    #[test]
+    fn test_array_of_structs_type() {
+        let typ = UnresolvedType::Array(
+            Some(Box::new(Expression::new(
+                ExpressionKind::Literal(Literal::Integer(2, None)),
+                Span::default(),
+            ))),
+            Box::new(UnresolvedType::Array(
+                Some(Box::new(Expression::new(
+                    ExpressionKind::Literal(Literal::Integer(3, None)),
+                    Span::default(),
+                ))),
+                Box::new(UnresolvedType::Named(noir_parser::Path {
+                    segments: vec![Ident {
+                        name: "Point".to_owned(),
+                        span: Span::default(),
+                    }],
+                })),
+            )),
+        );
+        let aleo_type = to_aleo_type(&typ).unwrap();
+        assert_eq!(aleo_type, "[[Point; 3u32]; 2u32]");
+        assert_eq!(aleo_array_layout(&aleo_type), Some(("[Point; 3u32]", 2)));
+    }
+
+    #[test]
+    fn test_non_literal_array_length_is_rejected() {
+        let error = compile_program(Path::new("tests/non_literal_array_length")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::NonLiteralArrayLength(Span::new(19, 23))
+        );
+    }
+
+    #[test]
+    fn test_method_calls() {
+        let aleo_program = compile_program(Path::new("tests/method_calls")).unwrap();
+        let expected = "program method_calls.aleo;
+
+transition main:
+    input r0 as u32.private;
+    pow r0 2u32 into r1;
+    output r1 as u32.private;
+
+transition invert:
+    input r0 as field.private;
+    inv r0 into r1;
+    output r1 as field.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_slice_parameter_is_rejected() {
+        let error = compile_program(Path::new("tests/slice_parameter")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::DynamicCollection("the slice type `[u32]`".to_owned())
+        );
+        assert!(error.to_string().contains("let mut len"));
+    }
+
+    #[test]
+    fn test_slice_push_is_rejected() {
+        let error = compile_program(Path::new("tests/slice_push")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::DynamicCollection("the method `push`".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_bounded_vec_lowers_pushed_slices_to_arrays() {
+        let compile = |body: &str| {
+            let source = format!("fn main(x : u32) -> pub u32 {{\n{body}\n}}\n");
+            let options = CompileOptions {
+                bounded_vec: Some(2),
+                ..CompileOptions::default()
+            };
+            compile_source(&source, "main", &options)
+                .map(|program| program.aleo_program)
+                .map_err(CompileError::without_location)
+        };
+        assert!(compile(
+            "    let mut xs: [u32] = [x];\n    xs.push(x + 1);\n    xs[1] + xs.len()"
+        )
+        .unwrap()
+        .ends_with("    add r0 1u32 into r1;\n    add r1 2u32 into r2;\n    output r2 as u32.public;\n"));
+        assert_eq!(
+            compile("    let mut xs: [u32] = [x, x];\n    xs.push(x);\n    xs.len()").unwrap_err(),
+            CompileError::IndexOutOfBounds {
+                index: 2,
+                length: 2
+            }
+        );
+        assert_eq!(
+            compile("    let mut xs: [u32] = [x];\n    xs[2]").unwrap_err(),
+            CompileError::IndexOutOfBounds {
+                index: 2,
+                length: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_if_statements_cannot_compute_operations_that_can_fail() {
+        let compile = |body: &str| {
+            let source = format!("fn main(x : u32, y : u32) -> pub u32 {{\n{body}\n}}\n");
+            compile_source(&source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(|error| error.to_string())
+        };
+        assert_eq!(
+            compile("    if x > y {\n        assert(x - y > 1);\n    }\n    x").unwrap_err(),
+            "main.nr:2:5: an `if` statement cannot compute an operation that can fail, since \
+             it is computed whether the condition holds or not"
+        );
+        assert!(
+            compile("    if x > y {\n        assert(x.wrapping_sub(y) > 1);\n    }\n    x")
+                .unwrap()
+                .contains("    sub.w r0 r1 into r3;\n")
+        );
+    }
+
+    #[test]
+    fn test_wrapping_methods_emit_the_wrapping_opcodes() {
+        let compile = |body: &str| {
+            let source = format!("fn main(x : u8, y : u8) -> pub u8 {{\n    {body}\n}}\n");
+            compile_source(&source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(CompileError::without_location)
+        };
+        assert!(compile("x * y")
+            .unwrap()
+            .ends_with("    mul r0 r1 into r2;\n    output r2 as u8.public;\n"));
+        assert!(compile("x.wrapping_mul(y)")
+            .unwrap()
+            .ends_with("    mul.w r0 r1 into r2;\n    output r2 as u8.public;\n"));
+        assert!(compile("x.wrapping_sub(1).wrapping_div(y)")
+            .unwrap()
+            .ends_with(
+            "    sub.w r0 1u8 into r2;\n    div.w r2 r1 into r3;\n    output r3 as u8.public;\n"
+        ));
+
+        let source = "fn main(x : Field) -> pub Field {\n    x.wrapping_add(x)\n}\n";
+        let error = compile_source(source, "main", &CompileOptions::default()).unwrap_err();
+        assert_eq!(
+            error.without_location(),
+            CompileError::UnsupportedBinaryOperand {
+                opcode: "wrapping_add",
+                operand_type: "field".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_std_wrapping_functions_emit_the_wrapping_opcodes() {
+        let compile = |body: &str| {
+            let source = format!("fn main(x : u8, y : u8) -> pub u8 {{\n    {body}\n}}\n");
+            compile_source(&source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(CompileError::without_location)
+        };
+        assert!(compile("x + y")
+            .unwrap()
+            .ends_with("    add r0 r1 into r2;\n    output r2 as u8.public;\n"));
+        assert!(compile("std::wrapping_add(x, y)")
+            .unwrap()
+            .ends_with("    add.w r0 r1 into r2;\n    output r2 as u8.public;\n"));
+        assert!(compile("std::wrapping_mul(std::wrapping_sub(x, 1), y)")
+            .unwrap()
+            .ends_with(
+                "    sub.w r0 1u8 into r2;\n    mul.w r2 r1 into r3;\n    output r3 as u8.public;\n"
+            ));
+        assert_eq!(
+            compile("std::wrapping_add(x)").unwrap_err(),
+            CompileError::ArgumentCountMismatch {
+                name: "std::wrapping_add".to_owned(),
+                expected: 2,
+                found: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_abs_emits_the_abs_opcodes() {
+        let compile = |source: &str| {
+            compile_source(source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(CompileError::without_location)
+        };
+        assert!(compile("fn main() -> pub i32 {\n    (-5i32).abs()\n}\n")
+            .unwrap()
+            .ends_with(
+                "    neg 5i32 into r0;\n    abs r0 into r1;\n    output r1 as i32.public;\n"
+            ));
+        assert!(
+            compile("fn main(x : i8) -> pub i8 {\n    x.wrapping_abs()\n}\n")
+                .unwrap()
+                .ends_with("    abs.w r0 into r1;\n    output r1 as i8.public;\n")
+        );
+        assert_eq!(
+            compile("fn main(x : u8) -> pub u8 {\n    x.abs()\n}\n").unwrap_err(),
+            CompileError::UnsupportedUnaryOperand {
+                opcode: "abs",
+                operand_type: "u8".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_pow_exponents_are_checked() {
+        let compile = |source: &str| {
+            compile_source(source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(CompileError::without_location)
+        };
+        assert!(
+            compile("fn main(x : u32) -> pub u32 {\n    x.pow(3u8)\n}\n")
+                .unwrap()
+                .ends_with("    pow r0 3u8 into r1;\n    output r1 as u32.public;\n")
+        );
+        assert!(
+            compile("fn main(x : i16) -> pub i16 {\n    x.wrapping_pow(3)\n}\n")
+                .unwrap()
+                .ends_with("    pow.w r0 3u32 into r1;\n    output r1 as i16.public;\n")
+        );
+        assert!(
+            compile("fn main(x : Field) -> pub Field {\n    x.pow(3)\n}\n")
+                .unwrap()
+                .ends_with("    pow r0 3field into r1;\n    output r1 as field.public;\n")
+        );
+        assert_eq!(
+            compile("fn main(x : u32) -> pub u32 {\n    x.pow(3u64)\n}\n").unwrap_err(),
+            CompileError::InvalidExponent {
+                base_type: "u32".to_owned(),
+                exponent_type: "u64".to_owned(),
+            }
+        );
+        assert_eq!(
+            compile("fn main(x : Field, n : u8) -> pub Field {\n    x.pow(n)\n}\n").unwrap_err(),
+            CompileError::InvalidExponent {
+                base_type: "field".to_owned(),
+                exponent_type: "u8".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_repeated_multiplications_collapse_into_pow_when_optimizing() {
+        let compile = |source: &str| {
+            let options = CompileOptions {
+                optimize: true,
+                ..CompileOptions::default()
+            };
+            compile_source(source, "main", &options)
+                .unwrap()
+                .aleo_program
+        };
+        assert!(
+            compile("fn main(x : Field) -> pub Field {\n    x * x * x * x\n}\n")
+                .ends_with("    pow r0 4field into r1;\n    output r1 as field.public;\n")
+        );
+        assert!(
+            compile("fn main(x : u64) -> pub u64 {\n    x * (x * x)\n}\n")
+                .ends_with("    pow r0 3u32 into r1;\n    output r1 as u64.public;\n")
+        );
+        assert!(compile(
+            "fn main(x : u64) -> pub u64 {\n    x.wrapping_mul(x).wrapping_mul(x)\n}\n"
+        )
+        .ends_with("    pow.w r0 3u32 into r1;\n    output r1 as u64.public;\n"));
+        assert!(
+            compile("fn main(x : Field) -> pub Field {\n    x.pow_32(3)\n}\n")
+                .ends_with("    pow r0 3field into r1;\n    output r1 as field.public;\n")
+        );
+        // The square is read twice, so it has to be computed anyway.
+        assert!(compile(
+            "fn main(x : u64) -> pub (u64, u64) {\n    let square = x * x;\n    (square, square * x)\n}\n"
+        )
+        .ends_with(
+            "    mul r0 r0 into r1;\n    mul r1 r0 into r2;\n    output r1 as u64.public;\n    output r2 as u64.public;\n"
+        ));
+        assert!(compile(
+            "fn main(x : u64) -> pub (u64, u64) {\n    let cube = x * x * x;\n    (cube, cube)\n}\n"
+        )
+        .ends_with(
+            "    pow r0 3u32 into r1;\n    output r1 as u64.public;\n    output r1 as u64.public;\n"
+        ));
+        assert!(compile_source(
+            "fn main(x : u64) -> pub u64 {\n    x * x * x\n}\n",
+            "main",
+            &CompileOptions::default()
+        )
+        .unwrap()
+        .aleo_program
+        .ends_with(
+            "    mul r0 r0 into r1;\n    mul r1 r0 into r2;\n    output r2 as u64.public;\n"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_method_lists_known_methods() {
+        let error = compile_program(Path::new("tests/unknown_method")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown method `cube`, expected one of: pow, pow_32, invert, square, sqrt, double, abs, wrapping_add, wrapping_sub, wrapping_mul, wrapping_div, wrapping_abs, wrapping_pow"
+        );
+    }
+
+    #[test]
+    fn test_conditional_constraint() {
+        let aleo_program = compile_program(Path::new("tests/conditional_constraint")).unwrap();
+        let expected = "program conditional_constraint.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as field.private;
+    input r2 as field.private;
+    input r3 as field.private;
+    call check r0 r0 into r4;
+    is.eq r1 r2 into r5;
+    is.eq r1 r3 into r6;
+    ternary r4 r5 r6 into r7;
+    assert.eq r7 true;
+    add r2 r3 into r8;
+    output r8 as field.private;
+
+function check:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    lt r0 r1 into r2;
+    output r2 as boolean.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_undeclared_variable_is_reported() {
+        let error = compile_program(Path::new("tests/undeclared_variable")).unwrap_err();
+        assert_eq!(error, CompileError::UndeclaredVariable("z".to_owned()));
+    }
+
+    #[test]
+    fn test_variable_used_before_its_let_is_reported() {
+        let source = "
+fn main(x : u32) -> pub u32 {
+    let y = z + x;
+    let z = x * 2u32;
+    y
+}
+";
+        let error = compile_source(source, "main", &CompileOptions::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "main.nr:3:13: `z` is used before the `let` binding it"
+        );
+        assert_eq!(
+            error.without_location(),
+            CompileError::UsedBeforeBinding("z".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_nested_let_bindings_are_collected_before_compiling() {
+        let compile = |body: &str| {
+            let source = format!("fn main(x : u32) -> pub u32 {{\n{body}\n}}\n");
+            compile_source(&source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(CompileError::without_location)
+        };
+        assert_eq!(
+            compile(
+                "    let y = {\n        let w = z;\n        let z = x;\n        w\n    };\n    y"
+            )
+            .unwrap_err(),
+            CompileError::UsedBeforeBinding("z".to_owned())
+        );
+        // A binding of a block that has ended is out of scope, not unbound yet.
+        assert_eq!(
+            compile("    let y = {\n        let z = x;\n        z\n    };\n    y + z").unwrap_err(),
+            CompileError::UndeclaredVariable("z".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_register_numbering_is_stable() {
+        let source = "
+fn main(x : u32, y : u32) -> pub u32 {
+    let a = y * x;
+    let b = {
+        let a = x + y;
+        a * y
+    };
+    let c = b * a;
+    a + c
+}
+";
+        let expected = "\
+program main.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    mul r1 r0 into r2;
+    add r0 r1 into r3;
+    mul r3 r1 into r4;
+    mul r4 r2 into r5;
+    add r2 r5 into r6;
+    output r6 as u32.public;
+";
+        for optimize in [false, true] {
+            let options = CompileOptions {
+                optimize,
+                ..CompileOptions::default()
+            };
+            for _ in 0..2 {
+                let program = compile_source(source, "main", &options).unwrap();
+                assert_eq!(program.aleo_program, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsupported_expression_is_reported() {
+        let error = compile_program(Path::new("tests/unsupported_for_loop")).unwrap_err();
+        assert_eq!(error, CompileError::UnsupportedExpression("for loop"));
+    }
+
+    #[test]
+    fn test_collect_diagnostics_reports_every_unsupported_construct() {
+        let package = resolve(Path::new("tests/unsupported_constructs")).unwrap();
+        let options = CompileOptions {
+            collect_diagnostics: true,
+            ..CompileOptions::default()
+        };
+        let error = compile(&package, &options).unwrap_err();
+        let CompileError::Unsupported(diagnostics) = &error else {
+            panic!("expected collected diagnostics, got {error:?}");
+        };
+        let reported: Vec<(&str, String)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.function.as_str(), diagnostic.error.to_string()))
+            .collect();
+        assert_eq!(
+            reported,
+            [
+                (
+                    "main",
+                    "tests/unsupported_constructs/src/main.nr:3:23: for loop expressions are not supported yet".to_owned()
+                ),
+                (
+                    "cubed",
+                    "tests/unsupported_constructs/src/main.nr:11:5: unknown method `cube`, expected one of: pow, pow_32, invert, square, sqrt, double, abs, wrapping_add, wrapping_sub, wrapping_mul, wrapping_div, wrapping_abs, wrapping_pow".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(error.to_string(), "2 unsupported constructs in 2 functions");
+    }
+
+    /// Builds the package in `program_dir` in collect mode, returning the
+    /// program made of what could be compiled and the errors found.
+    fn build_collecting(program_dir: &Path) -> (String, Vec<Diagnostic>) {
+        let package = resolve(program_dir).unwrap();
+        let mut diagnostics = Vec::new();
+        let aleo_program = build_aleo_program(
+            &package.noir_ast,
+            "main",
+            &Target::default(),
+            &mut Profile::default(),
+            Some(&mut diagnostics),
+        )
+        .unwrap();
+        (aleo_program.to_string(), diagnostics)
+    }
+
+    #[test]
+    fn test_poisoned_parameter_keeps_its_register() {
+        let (aleo_program, diagnostics) = build_collecting(Path::new("tests/poisoned_parameter"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].error.clone().without_location(),
+            CompileError::UnsupportedIntegerWidth("u24".to_owned())
+        );
+        let expected = "program main.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r2 as u32.private;
+    add r0 r2 into r3;
+    mul r3 r2 into r4;
+    output r4 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+
+        // Without the bad parameter, the program is the same but for the
+        // registers after it, which move down by the one it reserved.
+        let (clean_program, diagnostics) =
+            build_collecting(Path::new("tests/poisoned_parameter_removed"));
+        assert!(diagnostics.is_empty());
+        let renumbered: Vec<String> = aleo_program
+            .split(' ')
+            .map(|word| {
+                match word.strip_prefix('r').and_then(|rest| {
+                    let digits = rest.trim_end_matches(|c: char| !c.is_ascii_digit());
+                    Some((digits.parse::<u64>().ok()?, &rest[digits.len()..]))
+                }) {
+                    Some((index, rest)) if index >= 1 => format!("r{}{rest}", index - 1),
+                    _ => word.to_owned(),
+                }
+            })
+            .collect();
+        assert_eq!(renumbered.join(" "), clean_program);
+    }
+
+    #[test]
+    fn test_unsupported_constructs_stop_the_build_by_default() {
+        let error = compile_program(Path::new("tests/unsupported_constructs")).unwrap_err();
+        assert_eq!(error, CompileError::UnsupportedExpression("for loop"));
+    }
+
+    #[test]
+    fn test_errors_point_at_the_offending_line() {
+        let package = resolve(Path::new("tests/unsupported_for_loop")).unwrap();
+        let error = compile(&package, &CompileOptions::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "tests/unsupported_for_loop/src/main.nr:2:5: for loop expressions are not supported yet"
+        );
+    }
+
+    #[test]
+    fn test_missing_manifest_is_reported() {
+        let program_dir = tempfile::tempdir().unwrap();
+        let error = compile_to_aleo_instructions(
+            program_dir.path(),
+            &CompileOptions::default(),
+            Some(&OutputConfig::default()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::MissingManifest(program_dir.path().to_owned())
+        );
+    }
+
+    #[test]
+    fn test_boolean_constraints() {
+        let aleo_program = compile_program(Path::new("tests/boolean_constraints")).unwrap();
+        let expected = "program boolean_constraints.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    input r2 as boolean.private;
+    assert.eq r2 true;
+    call check r0 r1 into r3;
+    assert.eq r3 true;
+    lt r0 r1 into r4;
+    assert.eq r4 true;
+    add r0 r1 into r5;
+    mul r0 r1 into r6;
+    assert.eq r5 r6;
+    sub r1 r0 into r7;
+    output r7 as u32.private;
+
+function check:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    is.neq r0 r1 into r2;
+    output r2 as boolean.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_comparison_constraints() {
+        let aleo_program = compile_program(Path::new("tests/comparison_constraints")).unwrap();
+        let expected = "program comparison_constraints.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    lte r0 r1 into r2;
+    assert.eq r2 true;
+    gt r0 0u32 into r3;
+    assert.eq r3 true;
+    assert.neq 1u32 r1;
+    shl r1 2u32 into r4;
+    output r4 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_conjunction_constraints() {
+        let aleo_program = compile_program(Path::new("tests/conjunction_constraints")).unwrap();
+        let expected = "program conjunction_constraints.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    input r2 as u32.private;
+    input r3 as u32.private;
+    assert.eq r0 r1;
+    assert.neq r2 r3;
+    lt r0 r1 into r4;
+    lt r2 r3 into r5;
+    or r4 r5 into r6;
+    assert.eq r6 true;
+    add r0 r3 into r7;
+    output r7 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_non_boolean_constraint_is_rejected() {
+        let error = compile_program(Path::new("tests/non_boolean_constraint")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::TypeMismatch {
+                expected: "boolean".to_owned(),
+                found: "u32".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_written_program_is_normalized() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let aleo_program =
+            "program main.aleo;\r\n\r\nfunction main:\r\n    input r0 as u32.private;\n\n\n";
+        let compiled_program = CompiledProgram {
+            program_name: "main".to_owned(),
+            aleo_program: aleo_program.to_owned(),
+            ir: AleoProgram::new("main"),
+            description: Some("Adds two numbers.\r\n\r\nÀ la carte ✓".to_owned()),
+            abi: Vec::new(),
+            opcode_overrides: BTreeMap::new(),
+            test_vectors: Vec::new(),
+            skipped_tests: Vec::new(),
+            warnings: Vec::new(),
+            profile: Profile::default(),
+        };
+        let output_config = OutputConfig {
+            output_dir: output_dir.path().to_owned(),
+        };
+        let path = write(&compiled_program, &output_config).unwrap();
+        let expected = "// Adds two numbers.\n//\n// À la carte ✓\nprogram main.aleo;\n\nfunction main:\n    input r0 as u32.private;\n";
+        assert_eq!(fs::read(path).unwrap(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_two_functions_are_separated_by_one_blank_line() {
+        let aleo_program = compile_program(Path::new("tests/two_functions")).unwrap();
+        let expected = "program two_functions.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    add r0 r1 into r2;
+    output r2 as u32.private;
+
+transition sub:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    sub r0 r1 into r2;
+    output r2 as u32.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_render_program_without_items() {
+        assert_eq!(AleoProgram::new("main").to_string(), "program main.aleo;\n");
+    }
+
+    #[test]
+    fn test_render_separates_items_with_one_blank_line() {
+        let mut aleo_program = AleoProgram::new("main");
+        aleo_program.push_item(Item::Struct(Composite {
+            name: "point".to_owned(),
+            members: vec![("x".to_owned(), "u32".to_owned())],
+        }));
+        let mut function = AleoFunction::new(CallableKind::Function, "main");
+        function.inputs.push(Input {
+            register: Register(0),
+            typ: "point".to_owned(),
+            visibility: Some(Visibility::Private),
+        });
+        aleo_program.push_item(Item::Function(function));
+        assert_eq!(
+            aleo_program.to_string(),
+            "program main.aleo;\n\nstruct point:\n    x as u32;\n\nfunction main:\n    input r0 as point.private;\n"
+        );
+    }
+
+    #[test]
+    fn test_render_instructions() {
+        let register = |index| Operand::Register(Register(index));
+        let literal = |value| Operand::Integer {
+            value,
+            typ: "u32".to_owned(),
+        };
+        let instructions = [
+            Instruction::Operation {
+                opcode: Opcode::Add,
+                operands: vec![register(0), literal(1)],
+                destination: Register(1),
+            },
+            Instruction::Operation {
+                opcode: Opcode::Custom("rem".to_owned()),
+                operands: vec![register(0), register(1)],
+                destination: Register(2),
+            },
+            Instruction::Cast {
+                operands: vec![register(0), register(1)],
+                destination: Register(3),
+                typ: Some("[u32; 2u32]".to_owned()),
+            },
+            Instruction::Assert {
+                opcode: Opcode::AssertNeq,
+                lhs: Operand::Index {
+                    base: Box::new(register(3)),
+                    index: 1,
+                },
+                rhs: Operand::Member {
+                    base: Box::new(register(4)),
+                    member: "x".to_owned(),
+                },
+            },
+            Instruction::Call {
+                callee: "helper".to_owned(),
+                operands: vec![register(0)],
+                destinations: vec![Register(5), Register(6)],
+            },
+            Instruction::Call {
+                callee: "log".to_owned(),
+                operands: Vec::new(),
+                destinations: Vec::new(),
+            },
+            Instruction::GetOrUse {
+                mapping: "counters".to_owned(),
+                key: register(0),
+                default: Operand::Boolean(false),
+                destination: Register(7),
+            },
+            Instruction::Remove {
+                mapping: "counters".to_owned(),
+                key: literal(0),
+            },
+        ];
+        let rendered: Vec<String> = instructions.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            rendered,
+            [
+                "add r0 1u32 into r1",
+                "rem r0 r1 into r2",
+                "cast r0 r1 into r3 as [u32; 2u32]",
+                "assert.neq r3[1u32] r4.x",
+                "call helper r0 into r5 r6",
+                "call log",
+                "get.or_use counters[r0] false into r7",
+                "remove counters[0u32]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_closure_and_finalize_block() {
+        let mut closure = AleoFunction::new(CallableKind::Closure, "square");
+        closure.inputs.push(Input {
+            register: Register(0),
+            typ: "u32".to_owned(),
+            visibility: None,
+        });
+        closure.outputs.push(Output {
+            operand: Register(0).into(),
+            typ: "u32".to_owned(),
+            visibility: None,
+        });
+        assert_eq!(
+            closure.to_string(),
+            "closure square:\n    input r0 as u32;\n    output r0 as u32;\n"
+        );
+
+        let mut block = AleoFunction::new(CallableKind::Finalize, "main");
+        block.instructions.push(Instruction::Set {
+            value: Operand::Integer {
+                value: 1,
+                typ: "u64".to_owned(),
+            },
+            mapping: "counters".to_owned(),
+            key: Register(0).into(),
+        });
+        let mut transition = AleoFunction::new(CallableKind::Transition, "main");
+        transition.finalize = Some((vec![Register(0).into()], Box::new(block)));
+        assert_eq!(
+            transition.to_string(),
+            "transition main:\n    finalize r0;\nfinalize main:\n    set 1u64 into counters[r0];\n"
+        );
+    }
+
+    #[test]
+    fn test_normalized_output_without_metadata_ends_with_one_newline() {
+        assert_eq!(
+            normalize_aleo_output("program main.aleo;", None),
+            "program main.aleo;\n"
+        );
+        assert_eq!(
+            normalize_aleo_output("program main.aleo;\n\n", None),
+            "program main.aleo;\n"
+        );
+    }
+
+    #[test]
+    fn test_opcode_override() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let package = resolve(Path::new("tests/opcode_override")).unwrap();
+        let compiled_program = compile(&package, &CompileOptions::default()).unwrap();
+        let expected = "program opcode_override.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    rem r0 r1 into r2;
+    output r2 as u32.private;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+        let output_config = OutputConfig {
+            output_dir: output_dir.path().to_owned(),
+        };
+        write(&compiled_program, &output_config).unwrap();
+        assert_eq!(
+            fs::read_to_string(output_dir.path().join("manifest.json")).unwrap(),
+            "{\"opcode_overrides\": {\"mod\": \"rem\"}}\n"
+        );
+    }
+
+    #[test]
+    fn test_unknown_opcode_override() {
+        let overrides = BTreeMap::from([("hash.poseidon2".to_owned(), "hash.psd2".to_owned())]);
+        let error = OpcodeTable::new(&overrides).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::UnknownOpcodeOverride {
+                opcode: "hash.poseidon2".to_owned(),
+                overridable: OVERRIDABLE_OPCODES.to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_field_arithmetic() {
+        let aleo_program = compile_program(Path::new("tests/field_arithmetic")).unwrap();
+        let expected = "program field_arithmetic.aleo;
+
+transition main:
+    input r0 as field.private;
+    input r1 as field.private;
+    add r0 r1 into r2;
+    div r2 r1 into r3;
+    inv r1 into r4;
+    mul r3 r4 into r5;
+    sub r5 1field into r6;
+    output r6 as field.private;
+";
+        assert_eq!(aleo_program, expected);
+    }
+
+    #[test]
+    fn test_group_operations() {
+        let source = "
+fn main(point : Group) -> Group {
+    point.double()
+}
+
+fn combine(a : Group, b : Group) -> Group {
+    -(a + b)
+}
+";
+        let compiled_program =
+            compile_source(source, "group_operations", &CompileOptions::default()).unwrap();
+        let expected = "program group_operations.aleo;
+
+transition main:
+    input r0 as group.private;
+    double r0 into r1;
+    output r1 as group.private;
+
+transition combine:
+    input r0 as group.private;
+    input r1 as group.private;
+    add r0 r1 into r2;
+    neg r2 into r3;
+    output r3 as group.private;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+    }
+
+    #[test]
+    fn test_scalar_multiplication() {
+        let source = "
+fn main(point : Group, scalar : Scalar) -> Group {
+    point * scalar
+}
+
+fn scale_twice(point : Group, a : Scalar, b : Scalar) -> Group {
+    (a * b) * point
+}
+";
+        let compiled_program =
+            compile_source(source, "scalar_multiplication", &CompileOptions::default()).unwrap();
+        let expected = "program scalar_multiplication.aleo;
+
+transition main:
+    input r0 as group.private;
+    input r1 as scalar.private;
+    mul r0 r1 into r2;
+    output r2 as group.private;
+
+transition scale_twice:
+    input r0 as group.private;
+    input r1 as scalar.private;
+    input r2 as scalar.private;
+    mul r1 r2 into r3;
+    mul r3 r0 into r4;
+    output r4 as group.private;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+        assert!(to_aleo_opcode(BinaryOpKind::Multiply, Some("group"), Some("group")).is_err());
+    }
+
+    #[test]
+    fn test_address_inputs() {
+        let source = "
+fn main(sender : Address, receiver : pub Address) {
+    assert(sender == receiver);
+}
+";
+        let compiled_program =
+            compile_source(source, "address_inputs", &CompileOptions::default()).unwrap();
+        let expected = "program address_inputs.aleo;
+
+transition main:
+    input r0 as address.private;
+    input r1 as address.public;
+    assert.eq r0 r1;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+        assert_eq!(
+            to_aleo_opcode(BinaryOpKind::Add, Some("address"), Some("address")),
+            Err(CompileError::UnsupportedBinaryOperand {
+                opcode: "add",
+                operand_type: "address".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_constant_operations_are_folded() {
+        let source = "
+fn main(x : u32) -> pub u32 {
+    x + (2 * 3)
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        let expected = "program main.aleo;
+
+transition main:
+    input r0 as u32.private;
+    add r0 6u32 into r1;
+    output r1 as u32.public;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+
+        let source = "
+fn main(x : u32) -> pub u32 {
+    let y = 3u32 * 4u32;
+    x + (y - 2) * (2 * 3)
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        assert!(compiled_program
+            .aleo_program
+            .contains("    add r0 60u32 into r1;\n    output r1 as u32.public;\n"));
+    }
+
+    #[test]
+    fn test_global_constants_are_folded_where_they_are_read() {
+        let compile = |source: &str| {
+            compile_source(source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(|error| error.to_string())
+        };
+        let expected = "program main.aleo;
+
+transition main:
+    input r0 as u32.private;
+    mul r0 32u32 into r1;
+    output r1 as u32.public;
+";
+        assert_eq!(
+            compile("global SIZE = 8;\n\nfn main(x : u32) -> pub u32 {\n    x * (SIZE * 4)\n}\n")
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            compile(
+                "global SIZE: u32 = 8;\n\nfn main(x : u32) -> pub u32 {\n    x * (SIZE * 4)\n}\n"
+            )
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            compile(
+                "global A = B;\nglobal B = A;\n\nfn main(x : u32) -> pub u32 {\n    x + A\n}\n"
+            )
+            .unwrap_err(),
+            "main.nr:2:12: the value of global `A` depends on itself"
+        );
+    }
+
+    #[test]
+    fn test_integer_literals_must_fit_their_type() {
+        let compile = |body: &str| {
+            let source = format!("fn main(x : u8) -> pub u8 {{\n    {body}\n}}\n");
+            compile_source(&source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(|error| error.to_string())
+        };
+        assert_eq!(
+            compile("x + 300u8").unwrap_err(),
+            "main.nr:2:9: integer literal `300` does not fit in `u8`"
+        );
+        assert_eq!(
+            compile("x + 256").unwrap_err(),
+            "main.nr:2:9: integer literal `256` does not fit in `u8`"
+        );
+        assert!(compile("x + 255").is_ok());
+        assert_eq!(
+            compile("let y = 0i8 - 200i8;\n    x").unwrap_err(),
+            "main.nr:2:19: integer literal `200` does not fit in `i8`"
+        );
+        assert_eq!(
+            compile("let y = 99999999999999999999999u32;\n    x").unwrap_err(),
+            "main.nr:2:13: integer literal `99999999999999999999999` does not fit in `u32`"
+        );
+        assert_eq!(
+            compile_source(
+                "fn main(x : [u32; 99999999999999999999]) -> pub u32 {\n    x[0]\n}\n",
+                "main",
+                &CompileOptions::default()
+            )
+            .unwrap_err()
+            .to_string(),
+            "main.nr:1:19: integer literal `99999999999999999999` does not fit in `u32`"
+        );
+    }
+
+    #[test]
+    fn test_dead_code_is_eliminated() {
+        let source = "
+fn main(x : u32, y : u32) -> pub bool {
+    let smaller = x < y;
+    let sum = x + y;
+    let bigger = x > y;
+    bigger
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        let expected = "program main.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    gt r0 r1 into r2;
+    output r2 as boolean.public;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+    }
+
+    #[test]
+    fn test_common_subexpressions_are_computed_once_when_optimizing() {
+        let source = "
+fn main(a : u32, b : u32, c : u32) -> pub u32 {
+    constrain (a + b) < c;
+    let d = (a + b) * 2u32;
+    d
+}
+";
+        let optimized = compile_source(
+            source,
+            "main",
+            &CompileOptions {
+                optimize: true,
+                ..CompileOptions::default()
+            },
+        )
+        .unwrap();
+        assert!(optimized.aleo_program.ends_with(
+            "    add r0 r1 into r3;
+    lt r3 r2 into r4;
+    assert.eq r4 true;
+    mul r3 2u32 into r5;
+    output r5 as u32.public;
+"
+        ));
+        let unoptimized = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        assert!(unoptimized.aleo_program.ends_with(
+            "    add r0 r1 into r3;
+    lt r3 r2 into r4;
+    assert.eq r4 true;
+    add r0 r1 into r5;
+    mul r5 2u32 into r6;
+    output r6 as u32.public;
+"
+        ));
+    }
+
+    #[test]
+    fn test_unused_bindings_compile_away() {
+        let with_unused_bindings = "
+fn main(x : u32, y : u32, unused : u32) -> pub u32 {
+    let smaller = x < y;
+    let flags = [smaller, x == y];
+    x * y
+}
+";
+        let without_them = "
+fn main(x : u32, y : u32, unused : u32) -> pub u32 {
+    x * y
+}
+";
+        let [with_unused_bindings, without_them] =
+            [with_unused_bindings, without_them].map(|source| {
+                compile_source(source, "main", &CompileOptions::default())
+                    .unwrap()
+                    .aleo_program
+            });
+        assert_eq!(with_unused_bindings, without_them);
+        assert!(without_them.ends_with(
+            "    input r2 as u32.private;\n    mul r0 r1 into r3;\n    output r3 as u32.public;\n"
+        ));
+
+        let with_unused_arithmetic = "
+fn main(x : u32, y : u32, unused : u32) -> pub u32 {
+    let t = x + y;
+    let u = t * unused;
+    x * y
+}
+";
+        let with_unused_arithmetic =
+            compile_source(with_unused_arithmetic, "main", &CompileOptions::default())
+                .unwrap()
+                .aleo_program;
+        assert_eq!(with_unused_arithmetic, without_them);
+    }
+
+    #[test]
+    fn test_failing_constant_operations_are_kept() {
+        let mut function = AleoFunction::new(CallableKind::Function, "main");
+        let literal = |value| Operand::Integer {
+            value,
+            typ: "u8".to_owned(),
+        };
+        for (index, (opcode, lhs, rhs)) in [
+            (Opcode::Add, 255, 1),
+            (Opcode::Sub, 1, 2),
+            (Opcode::Lt, 1, 2),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            function.instructions.push(Instruction::Operation {
+                opcode,
+                operands: vec![literal(lhs), literal(rhs)],
+                destination: Register(index as u64),
+            });
+        }
+        let mut aleo_program = AleoProgram::new("main");
+        aleo_program.push_item(Item::Function(function));
+        optimize::fold_constants(&mut aleo_program).unwrap();
+        let expected = "program main.aleo;
+
+function main:
+    add 255u8 1u8 into r0;
+    sub 1u8 2u8 into r1;
+    cast true into r2 as boolean;
+";
+        assert_eq!(aleo_program.to_string(), expected);
+    }
+
+    #[test]
+    fn test_division_by_a_literal_zero_is_rejected() {
+        let source = "
+fn main(x : u32) -> pub u32 {
+    let zero = 1u32 - 1u32;
+    x / zero
+}
+";
+        let error = compile_source(source, "main", &CompileOptions::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "main.nr:4:5: `main` divides by zero, which always fails"
+        );
+        assert_eq!(
+            error.without_location(),
+            CompileError::DivisionByZero("main".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_output_is_the_tail_expression() {
+        let source = "
+fn main(x : u32, y : u32) -> pub u32 {
+    let sum = x + y;
+    let doubled = sum * 2u32;
+    sum
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        assert!(compiled_program
+            .aleo_program
+            .ends_with("    add r0 r1 into r2;\n    output r2 as u32.public;\n"));
+    }
+
+    #[test]
+    fn test_scope_stack_shadows_until_popped() {
+        let info = |index| RegisterInfo {
+            operand: Register(index).into(),
+            aleo_type: Some("u32".to_owned()),
+        };
+        let mut scopes = ScopeStack::new();
+        scopes.insert("x".to_owned(), info(0));
+        scopes.insert("y".to_owned(), info(1));
+        scopes.push_scope();
+        scopes.insert("x".to_owned(), info(2));
+        scopes.insert("z".to_owned(), info(3));
+        assert_eq!(scopes.get("x"), Some(&info(2)));
+        assert_eq!(scopes.get("y"), Some(&info(1)));
+        scopes.pop_scope();
+        assert_eq!(scopes.get("x"), Some(&info(0)));
+        assert_eq!(scopes.get("z"), None);
+    }
+
+    #[test]
+    fn test_bindings_keep_their_annotated_type() {
+        let source = "
+fn main(y : u8) -> pub u8 {
+    let x : u8 = 5;
+    let z : u8 = x * y;
+    z + 1
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        assert!(compiled_program.aleo_program.ends_with(
+            "    mul 5u8 r0 into r1;
+    add r1 1u8 into r2;
+    output r2 as u8.public;
+"
+        ));
+
+        let source = "
+fn main(y : u8) -> pub u8 {
+    let z : u32 = y;
+    y
+}
+";
+        let error = compile_source(source, "main", &CompileOptions::default()).unwrap_err();
+        assert_eq!(
+            error.without_location(),
+            CompileError::TypeMismatch {
+                expected: "u32".to_owned(),
+                found: "u8".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_blocks_scope_their_bindings() {
+        let source = "
+fn main(x : u32, y : u32) -> pub u32 {
+    let z = {
+        let x = x * y;
+        x + 1u32
+    };
+    x + z
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        assert!(compiled_program.aleo_program.ends_with(
+            "    mul r0 r1 into r2;\n    add r2 1u32 into r3;\n    add r0 r3 into r4;\n    output r4 as u32.public;\n"
+        ));
+    }
+
+    #[test]
+    fn test_nested_blocks_shadow_outer_bindings() {
+        let source = "
+fn main(x : u32) -> pub u32 {
+    let y = {
+        let x = x + 1u32;
+        let z = {
+            let x = x * 2u32;
+            x
+        };
+        x + z
+    };
+    x + y
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        assert!(compiled_program.aleo_program.ends_with(
+            "    add r0 1u32 into r1;
+    mul r1 2u32 into r2;
+    add r1 r2 into r3;
+    add r0 r3 into r4;
+    output r4 as u32.public;
+"
+        ));
+    }
+
+    #[test]
+    fn test_tuples_bound_to_a_name_are_lowered_to_structs() {
+        let source = "
+fn main(x : u32, y : Field) -> pub (Field, u32) {
+    let pair = (x * 2u32, y + y);
+    let (doubled, sum) = pair;
+    constrain pair.0 > x;
+    (sum, doubled)
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        let expected = "program main.aleo;
+
+struct Tuple2_u32_field:
+    item0 as u32;
+    item1 as field;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as field.private;
+    mul r0 2u32 into r2;
+    add r1 r1 into r3;
+    cast r2 r3 into r4 as Tuple2_u32_field;
+    gt r4.item0 r0 into r5;
+    assert.eq r5 true;
+    output r4.item1 as field.public;
+    output r4.item0 as u32.public;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+    }
+
+    #[test]
+    fn test_tuple_values_are_structs_declared_once_per_type() {
+        let source = "
+fn swap(pair : (u32, Field)) -> (Field, u32) {
+    let (a, b) = pair;
+    (b, a)
+}
+
+fn main(x : u32, y : Field) -> pub Field {
+    let pairs = [(x, y), (x + 1u32, y)];
+    let swapped = swap(pairs[1]);
+    let (first, second) = swapped;
+    constrain second == pairs[0].0 + 1u32;
+    first + swap((x, y)).0
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        let expected = "program main.aleo;
+
+struct Tuple2_u32_field:
+    item0 as u32;
+    item1 as field;
+
+struct Tuple2_field_u32:
+    item0 as field;
+    item1 as u32;
+
+function swap:
+    input r0 as u32.private;
+    input r1 as field.private;
+    output r1 as field.private;
+    output r0 as u32.private;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as field.private;
+    cast r0 r1 into r2 as Tuple2_u32_field;
+    add r0 1u32 into r3;
+    cast r3 r1 into r4 as Tuple2_u32_field;
+    cast r2 r4 into r5 as [Tuple2_u32_field; 2u32];
+    call swap r5[1u32].item0 r5[1u32].item1 into r6 r7;
+    cast r6 r7 into r8 as Tuple2_field_u32;
+    add r5[0u32].item0 1u32 into r9;
+    assert.eq r8.item1 r9;
+    call swap r0 r1 into r10 r11;
+    cast r10 r11 into r12 as Tuple2_field_u32;
+    add r8.item0 r12.item0 into r13;
+    output r13 as field.public;
+";
+        assert_eq!(compiled_program.aleo_program, expected);
+        assert_eq!(
+            tuple_struct_name(&["[u8; 4u32]".to_owned(), "boolean".to_owned()]),
+            "Tuple2_u8x4_boolean"
+        );
+    }
+
+    #[test]
+    fn test_comparisons_assert_only_at_the_top_of_a_constraint() {
+        let source = "
+fn main(a : u32, b : u32) -> pub bool {
+    constrain a != b;
+    let equal = a == b;
+    constrain equal == (a >= b);
+    a < b
+}
+";
+        let compiled_program = compile_source(source, "main", &CompileOptions::default()).unwrap();
+        assert!(compiled_program.aleo_program.ends_with(
+            "    assert.neq r0 r1;
+    is.eq r0 r1 into r2;
+    gte r0 r1 into r3;
+    assert.eq r2 r3;
+    lt r0 r1 into r4;
+    output r4 as boolean.public;
+"
+        ));
+        assert_eq!(
+            to_aleo_assertion(BinaryOpKind::Equal),
+            Some(Opcode::AssertEq)
+        );
+        assert_eq!(to_aleo_assertion(BinaryOpKind::Less), None);
+    }
+
+    #[test]
+    fn test_return_value_must_have_the_declared_type() {
+        let source = "
+fn main(x : u32, y : u32) -> pub u32 {
+    x < y
+}
+";
+        let error = compile_source(source, "main", &CompileOptions::default()).unwrap_err();
+        assert_eq!(
+            error.without_location(),
+            CompileError::TypeMismatch {
+                expected: "u32".to_owned(),
+                found: "boolean".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_integer_only_operators_are_rejected_on_fields() {
+        let error = compile_program(Path::new("tests/field_modulo")).unwrap_err();
+        assert_eq!(
+            error,
+            CompileError::UnsupportedBinaryOperand {
+                opcode: "mod",
+                operand_type: "field".to_owned(),
+            }
+        );
+        assert_eq!(
+            to_aleo_opcode(BinaryOpKind::Divide, Some("field"), Some("field")),
+            Ok("div")
+        );
+        assert!(to_aleo_opcode(BinaryOpKind::ShiftLeft, Some("field"), Some("field")).is_err());
+        assert_eq!(
+            to_aleo_opcode(BinaryOpKind::Modulo, Some("u32"), Some("u32")),
+            Ok("mod")
+        );
+        assert!(to_aleo_opcode(BinaryOpKind::Divide, Some("group"), Some("group")).is_err());
+        assert_eq!(
+            to_aleo_opcode(BinaryOpKind::Add, Some("group"), Some("group")),
+            Ok("add")
+        );
+    }
+
+    #[test]
+    fn test_binary_operands_must_have_the_same_type() {
+        let compile = |signature: &str, body: &str| {
+            let source = format!("fn main({signature}) -> pub u32 {{\n    {body}\n}}\n");
+            compile_source(&source, "main", &CompileOptions::default())
+                .map(|program| program.aleo_program)
+                .map_err(CompileError::without_location)
+        };
+        assert_eq!(
+            compile("x : u32, y : Field", "x + y").unwrap_err(),
+            CompileError::TypeMismatch {
+                expected: "u32".to_owned(),
+                found: "field".to_owned(),
+            }
+        );
+        assert_eq!(
+            compile("x : u32, y : u8", "x * y").unwrap_err(),
+            CompileError::TypeMismatch {
+                expected: "u32".to_owned(),
+                found: "u8".to_owned(),
+            }
+        );
+        assert_eq!(
+            compile("x : u32, y : u8", "constrain x == y;\n    x").unwrap_err(),
+            CompileError::TypeMismatch {
+                expected: "u32".to_owned(),
+                found: "u8".to_owned(),
+            }
+        );
+        assert!(compile("x : u32, y : u8", "x << y")
+            .unwrap()
+            .ends_with("    shl r0 r1 into r2;\n    output r2 as u32.public;\n"));
+        assert_eq!(
+            to_aleo_opcode(BinaryOpKind::Multiply, Some("scalar"), Some("group")),
+            Ok("mul")
+        );
+    }
+
+    /// A program where `main` calls `helper`, a callable of the given kind
+    /// and inputs, with operands of the given types.
+    fn program_with_call(
+        kind: CallableKind,
+        inputs: &[&str],
+        operand_types: &[&str],
+    ) -> AleoProgram {
+        let mut aleo_program = AleoProgram::new("main");
+        aleo_program.records.push("Token".to_owned());
+        aleo_program.signatures.insert(
+            "helper".to_owned(),
+            Signature {
+                kind,
+                inputs: inputs.iter().map(|input| Some(input.to_string())).collect(),
+            },
+        );
+        aleo_program.call_sites.push(CallSite {
+            caller: "main".to_owned(),
+            callee: "helper".to_owned(),
+            operand_types: operand_types
+                .iter()
+                .map(|operand_type| Some(operand_type.to_string()))
+                .collect(),
+            span: Span { start: 4, end: 10 },
+        });
+        aleo_program
+    }
+
+    fn violated_rule(aleo_program: &AleoProgram) -> Option<&'static str> {
+        match validator::validate(aleo_program, Dialect::SnarkVm) {
+            Ok(()) => None,
+            Err(CompileError::Spanned { error, span }) => {
+                assert_eq!(span, Span { start: 4, end: 10 });
+                match *error {
+                    CompileError::Internal { rule, .. } => Some(rule),
+                    error => panic!("expected an internal error, got {error:?}"),
+                }
+            }
+            Err(error) => panic!("expected a spanned error, got {error:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validator_accepts_matching_calls() {
+        let aleo_program =
+            program_with_call(CallableKind::Function, &["u32", "Token"], &["u32", "Token"]);
+        assert_eq!(violated_rule(&aleo_program), None);
+    }
+
+    #[test]
+    fn test_validator_rejects_mismatched_calls() {
+        let aleo_program = program_with_call(CallableKind::Function, &["u32", "u32"], &["u32"]);
+        assert_eq!(violated_rule(&aleo_program), Some("call-arity"));
+
+        let aleo_program = program_with_call(CallableKind::Function, &["u32"], &["field"]);
+        assert_eq!(violated_rule(&aleo_program), Some("call-operand-type"));
+        assert_eq!(
+            validator::validate(&aleo_program, Dialect::SnarkVm).unwrap_err().to_string(),
+            "internal compiler error [call-operand-type]: `main` passes a `field` as input 0 of `helper`, which is a `u32`"
+        );
+
+        let aleo_program = program_with_call(CallableKind::Closure, &["Token"], &["Token"]);
+        assert_eq!(violated_rule(&aleo_program), Some("closure-record-input"));
+
+        let mut aleo_program = program_with_call(CallableKind::Function, &[], &[]);
+        aleo_program.call_sites[0].callee = "missing".to_owned();
+        assert_eq!(violated_rule(&aleo_program), Some("call-target"));
+    }
+
+    #[test]
+    fn test_validator_accepts_the_boolean_asserts_of_the_dialect() {
+        let program_asserting = |instruction: Instruction| {
+            let mut function = AleoFunction::new(CallableKind::Function, "main");
+            function.instructions.push(instruction);
+            let mut aleo_program = AleoProgram::new("main");
+            aleo_program.items.push(Item::Function(function));
+            aleo_program
+        };
+        let compared = program_asserting(assert_true(Register(0).into(), Dialect::SnarkVm));
+        let native = program_asserting(assert_true(Register(0).into(), Dialect::Extended));
+        for dialect in [Dialect::SnarkVm, Dialect::Extended] {
+            assert_eq!(validator::validate(&compared, dialect), Ok(()));
+        }
+        assert_eq!(validator::validate(&native, Dialect::Extended), Ok(()));
+        assert_eq!(
+            validator::validate(&native, Dialect::SnarkVm)
+                .unwrap_err()
+                .to_string(),
+            "internal compiler error [assert-form]: `main` asserts `r0` with `assert`, which the \
+             target has no instruction for"
+        );
+    }
+
+    #[test]
+    fn test_compiled_calls_are_recorded_for_validation() {
+        let package = resolve(Path::new("tests/function_calls")).unwrap();
+        let aleo_program = build_aleo_program(
+            &package.noir_ast,
+            "function_calls",
+            &Target::default(),
+            &mut Profile::default(),
+            None,
+        )
+        .unwrap();
+        let calls: Vec<(&str, &str)> = aleo_program
+            .call_sites
+            .iter()
+            .map(|call_site| (call_site.caller.as_str(), call_site.callee.as_str()))
+            .collect();
+        assert_eq!(
+            calls,
+            [("main", "ping"), ("ping", "pong"), ("pong", "ping")]
+        );
+        assert_eq!(
+            aleo_program.signatures["ping"].inputs,
+            [Some("u32".to_owned()), Some("u32".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_to_aleo_operator_covers_every_binary_operator() {
+        let operators = [
+            (BinaryOpKind::Add, "add"),
+            (BinaryOpKind::Subtract, "sub"),
+            (BinaryOpKind::Multiply, "mul"),
+            (BinaryOpKind::Divide, "div"),
+            (BinaryOpKind::Equal, "is.eq"),
+            (BinaryOpKind::NotEqual, "is.neq"),
+            (BinaryOpKind::Less, "lt"),
+            (BinaryOpKind::LessEqual, "lte"),
+            (BinaryOpKind::Greater, "gt"),
+            (BinaryOpKind::GreaterEqual, "gte"),
+            (BinaryOpKind::And, "and"),
+            (BinaryOpKind::Or, "or"),
+            (BinaryOpKind::Xor, "xor"),
+            (BinaryOpKind::ShiftRight, "shr"),
+            (BinaryOpKind::ShiftLeft, "shl"),
+            (BinaryOpKind::Modulo, "mod"),
+        ];
+        for (operator, opcode) in operators {
+            assert_eq!(to_aleo_operator(operator), opcode);
+            assert!(OVERRIDABLE_OPCODES.contains(&opcode));
+        }
+    }
+}
@@ -1,3 +1,3 @@
 fn main() {
-    println!("Hello, world!");
+    noir_to_aleo_instructions::run(std::env::args_os());
 }
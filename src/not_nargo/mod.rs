@@ -0,0 +1,75 @@
+//! The slice of nargo's package handling that the compiler needs: locating
+//! a package's manifest and root file and parsing it.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use noir_parser::ParsedModule;
+
+use crate::error::CompileError;
+
+pub mod toml;
+
+const PKG_FILE: &str = "Nargo.toml";
+const SRC_DIR: &str = "src";
+pub const BINARY_ROOT_FILE: &str = "main.nr";
+
+pub fn find_package_manifest(program_dir: &Path) -> Result<PathBuf, CompileError> {
+    let manifest = program_dir.join(PKG_FILE);
+    if !manifest.is_file() {
+        return Err(CompileError::MissingManifest(program_dir.to_owned()));
+    }
+    Ok(manifest)
+}
+
+/// The closest directory containing a manifest, starting from `start` and
+/// going up, as nargo looks for the package it runs in.
+pub fn find_package_root(start: &Path) -> Result<PathBuf, CompileError> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(PKG_FILE).is_file())
+        .map(Path::to_owned)
+        .ok_or_else(|| CompileError::MissingManifest(start.to_owned()))
+}
+
+pub fn find_package_root_file(program_dir: &Path) -> PathBuf {
+    program_dir.join(SRC_DIR).join(BINARY_ROOT_FILE)
+}
+
+/// The name of the package in `program_dir`: the one in its manifest, or
+/// else the name of the directory itself.
+pub fn package_name(program_dir: &Path, config: &toml::Config) -> String {
+    if let Some(name) = &config.package.name {
+        return name.clone();
+    }
+    let program_dir = fs::canonicalize(program_dir).unwrap_or_else(|_| program_dir.to_owned());
+    program_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "main".to_owned())
+}
+
+/// Parses the root file of the package in `program_dir`, returning its AST
+/// and source.
+pub fn into_parsed_program(program_dir: &Path) -> Result<(ParsedModule, String), CompileError> {
+    let config = toml::parse(&find_package_manifest(program_dir)?)?;
+    if !config.dependencies.is_empty() {
+        return Err(CompileError::UnsupportedDependencies);
+    }
+    let root_file = find_package_root_file(program_dir);
+    let source = fs::read_to_string(&root_file).map_err(|error| CompileError::Io {
+        path: root_file.clone(),
+        message: error.to_string(),
+    })?;
+    let parsed_program = parse_root_file(&root_file, &source)?;
+    Ok((parsed_program, source))
+}
+
+/// Parses `source` as the contents of `root_file`, which only names it in
+/// errors.
+pub fn parse_root_file(root_file: &Path, source: &str) -> Result<ParsedModule, CompileError> {
+    noir_parser::parse_program(source)
+        .map_err(|error| CompileError::Parse(error).locate(root_file, source))
+}
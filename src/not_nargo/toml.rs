@@ -0,0 +1,97 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{error::CompileError, Dialect, Visibility};
+
+/// The contents of a package's `Nargo.toml`.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub package: PackageMetadata,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    pub aleo: AleoConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PackageMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Settings specific to the Aleo backend, under `[aleo]`.
+#[derive(Debug, Default, Deserialize)]
+pub struct AleoConfig {
+    /// Replacement opcodes, keyed by the opcode emitted by default.
+    #[serde(default)]
+    pub opcode_overrides: BTreeMap<String, String>,
+    pub default_visibility: Option<Visibility>,
+    pub dialect: Option<Dialect>,
+    /// The width struct casts are kept within, by moving their operands to
+    /// registers of their own.
+    pub max_line_width: Option<usize>,
+    /// The capacity append-only slices are lowered to fixed arrays of.
+    pub bounded_vec: Option<usize>,
+}
+
+/// The file of a package mapping functions to Aleo instructions.
+pub const INTRINSICS_FILE: &str = "aleo_intrinsics.toml";
+
+/// A function of `aleo_intrinsics.toml`, compiled to a single instruction.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IntrinsicEntry {
+    pub opcode: Spanned<String>,
+    /// The Aleo type of each argument.
+    pub arguments: Spanned<Vec<String>>,
+    /// The Aleo type of the result.
+    pub result: String,
+}
+
+pub fn parse(path_to_toml: &Path) -> Result<Config, CompileError> {
+    let contents = fs::read_to_string(path_to_toml).map_err(|error| CompileError::Io {
+        path: path_to_toml.to_owned(),
+        message: error.to_string(),
+    })?;
+    toml::from_str(&contents).map_err(|error| CompileError::InvalidManifest {
+        path: path_to_toml.to_owned(),
+        message: error.message().to_owned(),
+    })
+}
+
+/// A parsed `aleo_intrinsics.toml`, with what locating its errors takes.
+pub struct IntrinsicsFile {
+    pub path: PathBuf,
+    pub contents: String,
+    /// The entries, keyed by the path of their function.
+    pub entries: BTreeMap<String, IntrinsicEntry>,
+}
+
+/// The intrinsics file of the package in `program_dir`, which a package
+/// does not need.
+pub fn parse_intrinsics(program_dir: &Path) -> Result<Option<IntrinsicsFile>, CompileError> {
+    let path = program_dir.join(INTRINSICS_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(&path).map_err(|error| CompileError::Io {
+        path: path.clone(),
+        message: error.to_string(),
+    })?;
+    let entries = toml::from_str(&contents).map_err(|error| CompileError::InvalidManifest {
+        path: path.clone(),
+        message: error.message().to_owned(),
+    })?;
+    Ok(Some(IntrinsicsFile {
+        path,
+        contents,
+        entries,
+    }))
+}
@@ -0,0 +1,582 @@
+//! Passes rewriting the IR of a compiled program into an equivalent one with
+//! less work left for the prover.
+
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use crate::{
+    error::CompileError,
+    ir::{AleoFunction, AleoProgram, Instruction, Item, Opcode, Operand, Register},
+};
+
+/// The operations that cannot fail, whatever their operands.
+const INFALLIBLE_OPCODES: &[Opcode] = &[
+    Opcode::AddWrapped,
+    Opcode::SubWrapped,
+    Opcode::MulWrapped,
+    Opcode::AbsWrapped,
+    Opcode::PowWrapped,
+    Opcode::IsEq,
+    Opcode::IsNeq,
+    Opcode::Lt,
+    Opcode::Lte,
+    Opcode::Gt,
+    Opcode::Gte,
+    Opcode::And,
+    Opcode::Or,
+    Opcode::Xor,
+    Opcode::Not,
+    Opcode::Ternary,
+];
+
+/// The checked operations that can only fail by overflowing. Their result
+/// is all an unused `let` binding them would keep, so they are removed when
+/// it is not read.
+const OVERFLOWING_OPCODES: &[Opcode] = &[
+    Opcode::Add,
+    Opcode::Sub,
+    Opcode::Mul,
+    Opcode::Neg,
+    Opcode::Abs,
+    Opcode::Pow,
+    Opcode::Shl,
+    Opcode::Shr,
+];
+
+/// Computes at compile time what can be: every operation whose operands are
+/// all literals is replaced with a `cast` of its result, and the registers
+/// known to hold a literal are replaced with it in the instructions reading
+/// them. Operations that would fail at runtime, such as an
+/// overflowing `add`, are kept so that they still do, except for divisions
+/// by zero, which always fail and are reported instead.
+pub fn fold_constants(program: &mut AleoProgram) -> Result<(), CompileError> {
+    for item in &mut program.items {
+        if let Item::Function(function) = item {
+            fold_function_constants(function)?;
+        }
+    }
+    Ok(())
+}
+
+fn fold_function_constants(function: &mut AleoFunction) -> Result<(), CompileError> {
+    let mut constants: HashMap<Register, Operand> = HashMap::new();
+    for instruction in &mut function.instructions {
+        for operand in instruction.operands_mut() {
+            if let Some(literal) = operand
+                .as_register()
+                .and_then(|register| constants.get(register))
+            {
+                *operand = literal.clone();
+            }
+        }
+        match instruction {
+            Instruction::Operation {
+                opcode,
+                operands,
+                destination,
+            } => {
+                if let (Opcode::Div | Opcode::Mod, [_, Operand::Integer { value: 0, .. }]) =
+                    (&opcode, operands.as_slice())
+                {
+                    let error = CompileError::DivisionByZero(function.name.clone());
+                    return Err(match function.division_spans.get(destination) {
+                        Some(span) => error.with_span(*span),
+                        None => error,
+                    });
+                }
+                let Some(result) = evaluate(opcode, operands) else {
+                    continue;
+                };
+                let destination = *destination;
+                *instruction = Instruction::Cast {
+                    operands: vec![result.clone()],
+                    destination,
+                    typ: Some(literal_type(&result).to_owned()),
+                };
+                constants.insert(destination, result);
+            }
+            Instruction::Cast {
+                operands,
+                destination,
+                typ: Some(typ),
+            } => {
+                if let [literal @ (Operand::Integer { .. } | Operand::Boolean(_))] =
+                    operands.as_slice()
+                {
+                    if literal_type(literal) == typ {
+                        constants.insert(*destination, literal.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((_, block)) = &mut function.finalize {
+        fold_function_constants(block)?;
+    }
+    Ok(())
+}
+
+fn literal_type(literal: &Operand) -> &str {
+    match literal {
+        Operand::Integer { typ, .. } => typ,
+        _ => "boolean",
+    }
+}
+
+/// The result of `opcode` on `literals`, if it is a literal Aleo would
+/// compute without failing.
+fn evaluate(opcode: &Opcode, operands: &[Operand]) -> Option<Operand> {
+    match operands {
+        [Operand::Boolean(lhs), Operand::Boolean(rhs)] => {
+            let result = match opcode {
+                Opcode::And => lhs & rhs,
+                Opcode::Or => lhs | rhs,
+                Opcode::Xor | Opcode::IsNeq => lhs ^ rhs,
+                Opcode::IsEq => lhs == rhs,
+                _ => return None,
+            };
+            Some(Operand::Boolean(result))
+        }
+        [Operand::Boolean(value)] if *opcode == Opcode::Not => Some(Operand::Boolean(!value)),
+        [Operand::Integer {
+            value: lhs,
+            typ: lhs_type,
+        }, Operand::Integer {
+            value: rhs,
+            typ: rhs_type,
+        }] if lhs_type == rhs_type => {
+            let comparison = match opcode {
+                Opcode::IsEq => Some(lhs == rhs),
+                Opcode::IsNeq => Some(lhs != rhs),
+                Opcode::Lt => Some(lhs < rhs),
+                Opcode::Lte => Some(lhs <= rhs),
+                Opcode::Gt => Some(lhs > rhs),
+                Opcode::Gte => Some(lhs >= rhs),
+                _ => None,
+            };
+            if let Some(result) = comparison {
+                // Literals are never negative, so they compare as they do
+                // in every Aleo type, groups aside.
+                return (lhs_type != "group").then_some(Operand::Boolean(result));
+            }
+            let max = max_value(lhs_type)?;
+            let is_integer = lhs_type != "field" && lhs_type != "scalar";
+            let value = match opcode {
+                Opcode::Add => lhs.checked_add(*rhs),
+                Opcode::Sub if is_integer => lhs.checked_sub(*rhs),
+                Opcode::Mul => lhs.checked_mul(*rhs),
+                Opcode::Div if is_integer => lhs.checked_div(*rhs),
+                Opcode::Mod if is_integer && lhs_type.starts_with('u') => lhs.checked_rem(*rhs),
+                _ => None,
+            }
+            .filter(|value| *value <= max)?;
+            Some(Operand::Integer {
+                value,
+                typ: lhs_type.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The largest value `typ` holds that a `u128` can represent, for the types
+/// whose arithmetic is folded.
+pub(crate) fn max_value(typ: &str) -> Option<u128> {
+    if typ == "field" || typ == "scalar" {
+        // Both moduli are far above `u128::MAX`.
+        return Some(u128::MAX);
+    }
+    let (signed, bits) = typ.split_at(1);
+    let bits: u32 = bits.parse().ok()?;
+    if !matches!(bits, 8 | 16 | 32 | 64 | 128) {
+        return None;
+    }
+    match signed {
+        "u" => Some(u128::MAX >> (128 - bits)),
+        "i" => Some(u128::MAX >> (129 - bits)),
+        _ => None,
+    }
+}
+
+/// What an operation or cast computes, whatever register it writes it to.
+#[derive(PartialEq, Eq, Hash)]
+enum Computation {
+    Operation(Opcode, Vec<Operand>),
+    Cast(Vec<Operand>, Option<String>),
+}
+
+/// Removes every operation or cast computing the same as an earlier one of
+/// its function, whose result is read instead. Two identical instructions
+/// fail on the same operands, so only the first one is needed. Records are
+/// still built once per cast, since each is a distinct record.
+pub fn eliminate_common_subexpressions(program: &mut AleoProgram) {
+    for item in &mut program.items {
+        if let Item::Function(function) = item {
+            eliminate_function_common_subexpressions(function);
+        }
+    }
+}
+
+fn eliminate_function_common_subexpressions(function: &mut AleoFunction) {
+    let mut computed: HashMap<Computation, Register> = HashMap::new();
+    let mut replacements: HashMap<Register, Register> = HashMap::new();
+    let replace = |operand: &mut Operand, replacements: &HashMap<Register, Register>| {
+        if let Some(register) = operand.register_mut() {
+            if let Some(replacement) = replacements.get(register) {
+                *register = *replacement;
+            }
+        }
+    };
+    function.instructions.retain_mut(|instruction| {
+        for operand in instruction.operands_mut() {
+            replace(operand, &replacements);
+        }
+        let (computation, destination) = match instruction {
+            Instruction::Operation {
+                opcode,
+                operands,
+                destination,
+            } => (
+                Computation::Operation(opcode.clone(), operands.clone()),
+                *destination,
+            ),
+            Instruction::Cast {
+                operands,
+                destination,
+                typ,
+            } if !typ.as_ref().is_some_and(|typ| typ.ends_with(".record")) => (
+                Computation::Cast(operands.clone(), typ.clone()),
+                *destination,
+            ),
+            _ => return true,
+        };
+        match computed.entry(computation) {
+            Entry::Occupied(entry) => {
+                replacements.insert(destination, *entry.get());
+                false
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(destination);
+                true
+            }
+        }
+    });
+    for output in &mut function.outputs {
+        replace(&mut output.operand, &replacements);
+    }
+    if let Some((operands, block)) = &mut function.finalize {
+        for operand in operands {
+            replace(operand, &replacements);
+        }
+        eliminate_function_common_subexpressions(block);
+    }
+    renumber_registers(function);
+}
+
+/// The operations whose result has the type of their first operand.
+const SAME_TYPE_OPCODES: &[Opcode] = &[
+    Opcode::Add,
+    Opcode::Sub,
+    Opcode::Mul,
+    Opcode::Div,
+    Opcode::AddWrapped,
+    Opcode::SubWrapped,
+    Opcode::MulWrapped,
+    Opcode::DivWrapped,
+    Opcode::Mod,
+    Opcode::Neg,
+    Opcode::Abs,
+    Opcode::AbsWrapped,
+    Opcode::Pow,
+    Opcode::PowWrapped,
+    Opcode::Inv,
+    Opcode::Square,
+    Opcode::SquareRoot,
+    Opcode::Double,
+];
+
+/// A register multiplied by itself `exponent` times, by the instructions at
+/// `multiplications`.
+#[derive(Clone)]
+struct Power {
+    base: Register,
+    exponent: u128,
+    opcode: Opcode,
+    multiplications: Vec<usize>,
+}
+
+/// Replaces each chain of multiplications of a register by itself, such as
+/// `x * x * x`, with a single `pow` raising it to the number of factors. A
+/// chain is only collapsed if nothing else reads its intermediate products,
+/// and only for integers, whose exponent is a `u32`, and fields. Checked
+/// multiplications become a checked `pow`, which overflows whenever one of
+/// them would, and wrapping ones a `pow.w`.
+pub fn collapse_repeated_multiplications(program: &mut AleoProgram) {
+    for item in &mut program.items {
+        if let Item::Function(function) = item {
+            collapse_function_repeated_multiplications(function);
+        }
+    }
+}
+
+fn collapse_function_repeated_multiplications(function: &mut AleoFunction) {
+    let mut reads: HashMap<Register, usize> = HashMap::new();
+    let outputs = function.outputs.iter().map(|output| &output.operand);
+    let finalize_operands = function
+        .finalize
+        .iter()
+        .flat_map(|(operands, _)| operands.iter());
+    for register in outputs
+        .chain(finalize_operands)
+        .filter_map(Operand::register)
+    {
+        *reads.entry(register).or_default() += 1;
+    }
+    for instruction in &mut function.instructions {
+        for register in instruction
+            .operands_mut()
+            .iter()
+            .filter_map(|operand| operand.register())
+        {
+            *reads.entry(register).or_default() += 1;
+        }
+    }
+
+    let mut types: HashMap<Register, String> = function
+        .inputs
+        .iter()
+        .map(|input| (input.register, input.typ.clone()))
+        .collect();
+    let mut powers: HashMap<Register, Power> = HashMap::new();
+    for (index, instruction) in function.instructions.iter().enumerate() {
+        let (opcode, operands, destination) = match instruction {
+            Instruction::Operation {
+                opcode,
+                operands,
+                destination,
+            } => (opcode, operands, *destination),
+            Instruction::Cast {
+                destination,
+                typ: Some(typ),
+                ..
+            } => {
+                types.insert(*destination, typ.clone());
+                continue;
+            }
+            _ => continue,
+        };
+        if SAME_TYPE_OPCODES.contains(opcode) {
+            let typ = match operands.first() {
+                Some(Operand::Integer { typ, .. }) => Some(typ.clone()),
+                Some(operand) => operand
+                    .as_register()
+                    .and_then(|register| types.get(register))
+                    .cloned(),
+                None => None,
+            };
+            if let Some(typ) = typ {
+                types.insert(destination, typ);
+            }
+        }
+        let (Opcode::Mul | Opcode::MulWrapped, [Operand::Register(lhs), Operand::Register(rhs)]) =
+            (opcode, operands.as_slice())
+        else {
+            continue;
+        };
+        let factor = |register: &Register| match powers.get(register) {
+            Some(power) if reads[register] == 1 && power.opcode == *opcode => power.clone(),
+            _ => Power {
+                base: *register,
+                exponent: 1,
+                opcode: opcode.clone(),
+                multiplications: Vec::new(),
+            },
+        };
+        let (lhs_factor, rhs_factor) = (factor(lhs), factor(rhs));
+        if lhs_factor.base != rhs_factor.base {
+            continue;
+        }
+        for (register, factor) in [(lhs, &lhs_factor), (rhs, &rhs_factor)] {
+            if !factor.multiplications.is_empty() {
+                powers.remove(register);
+            }
+        }
+        let mut multiplications = lhs_factor.multiplications;
+        multiplications.extend(rhs_factor.multiplications);
+        multiplications.push(index);
+        powers.insert(
+            destination,
+            Power {
+                exponent: lhs_factor.exponent + rhs_factor.exponent,
+                multiplications,
+                ..lhs_factor
+            },
+        );
+    }
+
+    let mut removed = HashSet::new();
+    for (destination, power) in &powers {
+        let [rest @ .., last] = power.multiplications.as_slice() else {
+            continue;
+        };
+        if rest.is_empty() {
+            continue;
+        }
+        let exponent_type = match types.get(&power.base).map(String::as_str) {
+            Some("field") => "field",
+            Some(typ) if typ != "scalar" && max_value(typ).is_some() => "u32",
+            _ => continue,
+        };
+        function.instructions[*last] = Instruction::Operation {
+            opcode: match power.opcode {
+                Opcode::MulWrapped => Opcode::PowWrapped,
+                _ => Opcode::Pow,
+            },
+            operands: vec![
+                Operand::Register(power.base),
+                Operand::Integer {
+                    value: power.exponent,
+                    typ: exponent_type.to_owned(),
+                },
+            ],
+            destination: *destination,
+        };
+        removed.extend(rest.iter().copied());
+    }
+    let mut index = 0;
+    function.instructions.retain(|_| {
+        index += 1;
+        !removed.contains(&(index - 1))
+    });
+    if let Some((_, block)) = &mut function.finalize {
+        collapse_function_repeated_multiplications(block);
+    }
+    renumber_registers(function);
+}
+
+/// Removes the instructions whose results are never used, then numbers the
+/// registers left after the inputs again so that they follow each other.
+/// Unused checked arithmetic goes too, but a division or a narrowing `cast`
+/// stays even when its result is unused, since it is how a program checks
+/// that a divisor is not zero or a value fits a type. So do asserts, calls
+/// and mapping updates.
+pub fn eliminate_dead_code(program: &mut AleoProgram) {
+    let composites: HashSet<String> = program
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Struct(composite) | Item::Record(composite) => Some(composite.name.clone()),
+            Item::Mapping(_) | Item::Function(_) => None,
+        })
+        .collect();
+    for item in &mut program.items {
+        if let Item::Function(function) = item {
+            eliminate_function_dead_code(function, &composites);
+        }
+    }
+}
+
+fn eliminate_function_dead_code(function: &mut AleoFunction, composites: &HashSet<String>) {
+    let mut live: HashSet<Register> = function
+        .outputs
+        .iter()
+        .filter_map(|output| output.operand.register())
+        .collect();
+    if let Some((operands, block)) = &mut function.finalize {
+        live.extend(operands.iter().filter_map(Operand::register));
+        eliminate_function_dead_code(block, composites);
+    }
+    let mut kept = Vec::new();
+    for mut instruction in function.instructions.drain(..).rev() {
+        let is_used = !is_removable(&instruction, composites)
+            || instruction
+                .destinations_mut()
+                .iter()
+                .any(|destination| live.contains(destination));
+        if is_used {
+            live.extend(
+                instruction
+                    .operands_mut()
+                    .iter()
+                    .filter_map(|operand| operand.register()),
+            );
+            kept.push(instruction);
+        }
+    }
+    kept.reverse();
+    function.instructions = kept;
+    renumber_registers(function);
+}
+
+/// Whether `instruction` can fail for some values of its operands, other
+/// than by asserting.
+pub(crate) fn can_fail(instruction: &Instruction, composites: &HashSet<String>) -> bool {
+    match instruction {
+        Instruction::Operation { opcode, .. } => !INFALLIBLE_OPCODES.contains(opcode),
+        Instruction::Cast { .. } => !is_removable(instruction, composites),
+        _ => false,
+    }
+}
+
+/// Whether `instruction` only computes its destinations, without any effect
+/// or way to fail that removing it would lose.
+fn is_removable(instruction: &Instruction, composites: &HashSet<String>) -> bool {
+    match instruction {
+        Instruction::Operation { opcode, .. } => {
+            INFALLIBLE_OPCODES.contains(opcode) || OVERFLOWING_OPCODES.contains(opcode)
+        }
+        // Building a struct or an array cannot fail, nor can casting a
+        // literal into its own type.
+        Instruction::Cast {
+            operands,
+            typ: Some(typ),
+            ..
+        } => {
+            composites.contains(typ.trim_end_matches(".record"))
+                || typ.starts_with('[')
+                || matches!(operands.as_slice(), [literal] if literal_type(literal) == typ
+                    && matches!(literal, Operand::Integer { .. } | Operand::Boolean(_)))
+        }
+        Instruction::Contains { .. } | Instruction::GetOrUse { .. } => true,
+        _ => false,
+    }
+}
+
+fn renumber_registers(function: &mut AleoFunction) {
+    let mut numbers: HashMap<Register, Register> = function
+        .inputs
+        .iter()
+        .map(|input| (input.register, input.register))
+        .collect();
+    let mut next = function
+        .inputs
+        .iter()
+        .map(|input| input.register.0 + 1)
+        .max()
+        .unwrap_or(0);
+    for instruction in &mut function.instructions {
+        for operand in instruction.operands_mut() {
+            if let Some(register) = operand.register_mut() {
+                *register = numbers[register];
+            }
+        }
+        for destination in instruction.destinations_mut() {
+            numbers.insert(*destination, Register(next));
+            *destination = Register(next);
+            next += 1;
+        }
+    }
+    let outputs = function
+        .outputs
+        .iter_mut()
+        .map(|output| &mut output.operand);
+    let finalize_operands = function
+        .finalize
+        .iter_mut()
+        .flat_map(|(operands, _)| operands.iter_mut());
+    for operand in outputs.chain(finalize_operands) {
+        if let Some(register) = operand.register_mut() {
+            *register = numbers[register];
+        }
+    }
+}
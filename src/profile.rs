@@ -0,0 +1,179 @@
+//! Wall-clock timings of the compiler's phases, reported by `--profile`.
+//! With the `alloc-profile` feature, the global allocator also counts the
+//! bytes allocated, and each phase records the most it held at once.
+
+use std::{
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "alloc-profile")]
+#[global_allocator]
+static ALLOCATOR: allocator::CountingAllocator = allocator::CountingAllocator;
+
+/// The time spent in each phase of a build.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub entries: Vec<ProfileEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileEntry {
+    pub name: String,
+    pub duration: Duration,
+    /// The most bytes allocated at once during the phase, beyond those
+    /// allocated when it started. Only counted with the `alloc-profile`
+    /// feature.
+    pub peak_bytes: Option<usize>,
+}
+
+impl Profile {
+    /// Runs `phase` and records how long it took under `name`.
+    pub fn time<T>(&mut self, name: impl Into<String>, phase: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "alloc-profile")]
+        let peak = allocator::Peak::start();
+        let start = Instant::now();
+        let result = phase();
+        let duration = start.elapsed();
+        #[cfg(feature = "alloc-profile")]
+        let peak_bytes = Some(peak.finish());
+        #[cfg(not(feature = "alloc-profile"))]
+        let peak_bytes = None;
+        self.entries.push(ProfileEntry {
+            name: name.into(),
+            duration,
+            peak_bytes,
+        });
+        result
+    }
+
+    /// Moves the entries of `other` after those of this profile.
+    pub fn append(&mut self, other: &mut Profile) {
+        self.entries.append(&mut other.entries);
+    }
+
+    /// Renders the entries as a table, slowest first.
+    pub fn to_table(&self) -> String {
+        let mut entries: Vec<&ProfileEntry> = self.entries.iter().collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.duration));
+        let width = entries
+            .iter()
+            .map(|entry| entry.name.len())
+            .max()
+            .unwrap_or(0);
+        let mut table = String::new();
+        for entry in entries {
+            let micros = entry.duration.as_secs_f64() * 1e6;
+            write!(table, "{:<width$}  {micros:>10.1} µs", entry.name).unwrap();
+            if let Some(peak_bytes) = entry.peak_bytes {
+                let kibibytes = peak_bytes as f64 / 1024.0;
+                write!(table, "  {kibibytes:>10.1} KiB peak").unwrap();
+            }
+            table.push('\n');
+        }
+        table
+    }
+
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let name = entry.name.replace('\\', "\\\\").replace('"', "\\\"");
+                let peak_bytes = entry
+                    .peak_bytes
+                    .map(|peak_bytes| format!(", \"peak_bytes\": {peak_bytes}"))
+                    .unwrap_or_default();
+                format!(
+                    "{{\"name\": \"{name}\", \"nanoseconds\": {}{peak_bytes}}}",
+                    entry.duration.as_nanos()
+                )
+            })
+            .collect();
+        format!("{{\"phases\": [{}]}}\n", entries.join(", "))
+    }
+}
+
+#[cfg(feature = "alloc-profile")]
+mod allocator {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    /// The bytes allocated now, and the most allocated at once since the
+    /// current phase started.
+    static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+    static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+    /// The system allocator, counting the bytes it hands out.
+    pub struct CountingAllocator;
+
+    impl CountingAllocator {
+        fn grow(size: usize) {
+            let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+
+        fn shrink(size: usize) {
+            ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+        }
+    }
+
+    // SAFETY: every call is forwarded to `System` unchanged.
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let pointer = System.alloc(layout);
+            if !pointer.is_null() {
+                Self::grow(layout.size());
+            }
+            pointer
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let pointer = System.alloc_zeroed(layout);
+            if !pointer.is_null() {
+                Self::grow(layout.size());
+            }
+            pointer
+        }
+
+        unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+            System.dealloc(pointer, layout);
+            Self::shrink(layout.size());
+        }
+
+        unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_pointer = System.realloc(pointer, layout, new_size);
+            if !new_pointer.is_null() {
+                Self::shrink(layout.size());
+                Self::grow(new_size);
+            }
+            new_pointer
+        }
+    }
+
+    /// The peak of a phase being profiled. Phases nest, so the peak of the
+    /// phase around it is restored once it finishes.
+    pub struct Peak {
+        allocated: usize,
+        outer_peak: usize,
+    }
+
+    impl Peak {
+        pub fn start() -> Self {
+            let allocated = ALLOCATED.load(Ordering::Relaxed);
+            Peak {
+                allocated,
+                outer_peak: PEAK.swap(allocated, Ordering::Relaxed),
+            }
+        }
+
+        /// The most bytes allocated at once since `start`, beyond those
+        /// allocated then.
+        pub fn finish(self) -> usize {
+            let peak = PEAK.fetch_max(self.outer_peak, Ordering::Relaxed);
+            peak.saturating_sub(self.allocated)
+        }
+    }
+}
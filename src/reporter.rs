@@ -0,0 +1,52 @@
+//! Everything the command line prints goes through a [`Reporter`], which
+//! keeps stdout for results that scripts can consume, the written path or the
+//! program itself, and sends progress, notes and errors to stderr.
+
+use crate::error::CompileError;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reporter {
+    /// Silences everything but results and errors.
+    pub quiet: bool,
+}
+
+impl Reporter {
+    /// Prints a result of the command to stdout, whatever the verbosity.
+    pub fn result(&self, text: &str) {
+        if text.ends_with('\n') {
+            print!("{text}");
+        } else {
+            println!("{text}");
+        }
+    }
+
+    /// Reports progress to stderr.
+    pub fn status(&self, message: &str) {
+        if !self.quiet {
+            eprintln!("{message}");
+        }
+    }
+
+    pub fn warning(&self, message: &str) {
+        if !self.quiet {
+            eprintln!("warning: {message}");
+        }
+    }
+
+    pub fn note(&self, message: &str) {
+        if !self.quiet {
+            eprintln!("note: {message}");
+        }
+    }
+
+    /// Prints `error` to stderr, listing each of the errors it collected
+    /// first. Errors are printed even when quiet.
+    pub fn error(&self, error: &CompileError) {
+        if let CompileError::Unsupported(diagnostics) = error {
+            for diagnostic in diagnostics {
+                eprintln!("error: {}", diagnostic.error);
+            }
+        }
+        eprintln!("error: {error}");
+    }
+}
@@ -0,0 +1,148 @@
+//! Execution fixtures extracted from the `#[test]` functions of a package,
+//! emitted by `--emit test-vectors`.
+//!
+//! Only tests that call a function of the program with literal arguments and
+//! assert on its result with `==` can be turned into a vector. The others are
+//! skipped, with the reason why.
+
+use noir_parser::{
+    BinaryOpKind, CallExpression, ConstrainStatement, Expression, ExpressionKind,
+    FunctionDefinition, LetStatement, Literal, ParsedModule, Pattern, Statement, UnresolvedType,
+};
+
+use crate::{is_test, to_aleo_type};
+
+/// The inputs of one execution of a program function and the outputs it
+/// must produce, as Aleo literals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    pub name: String,
+    pub function: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+}
+
+impl TestVector {
+    pub fn to_json(&self) -> String {
+        let quote = |literals: &[String]| {
+            literals
+                .iter()
+                .map(|literal| format!("\"{literal}\""))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        format!(
+            "{{\"function\": \"{}\", \"inputs\": [{}], \"outputs\": [{}]}}\n",
+            self.function,
+            quote(&self.inputs),
+            quote(&self.outputs)
+        )
+    }
+}
+
+/// A test that does not have the shape of a test vector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedTest {
+    pub name: String,
+    pub reason: &'static str,
+}
+
+pub fn extract(noir_ast: &ParsedModule) -> (Vec<TestVector>, Vec<SkippedTest>) {
+    let mut test_vectors = Vec::new();
+    let mut skipped_tests = Vec::new();
+    for test in noir_ast
+        .functions
+        .iter()
+        .filter(|function| is_test(function))
+    {
+        match extract_test_vector(test, noir_ast) {
+            Ok(test_vector) => test_vectors.push(test_vector),
+            Err(reason) => skipped_tests.push(SkippedTest {
+                name: test.name.name.clone(),
+                reason,
+            }),
+        }
+    }
+    (test_vectors, skipped_tests)
+}
+
+fn extract_test_vector(
+    test: &FunctionDefinition,
+    noir_ast: &ParsedModule,
+) -> Result<TestVector, &'static str> {
+    let mut call: Option<&CallExpression> = None;
+    let mut result_name = None;
+    let mut expected = None;
+    for statement in &test.body.0 {
+        match statement {
+            Statement::Let(LetStatement {
+                pattern: Pattern::Identifier(ident),
+                expression:
+                    Expression {
+                        kind: ExpressionKind::Call(let_call),
+                        ..
+                    },
+                ..
+            }) if call.is_none() => {
+                call = Some(let_call);
+                result_name = Some(&ident.name);
+            }
+            Statement::Constrain(ConstrainStatement(Expression {
+                kind: ExpressionKind::Infix(infix),
+                ..
+            })) if infix.operator == BinaryOpKind::Equal && expected.is_none() => {
+                match &infix.lhs.kind {
+                    ExpressionKind::Call(constrained_call) if call.is_none() => {
+                        call = Some(constrained_call);
+                    }
+                    ExpressionKind::Path(path)
+                        if path.as_ident().map(|ident| &ident.name) == result_name => {}
+                    _ => return Err("it asserts on something other than the result of the call"),
+                }
+                expected = Some(&infix.rhs);
+            }
+            _ => return Err("it is not a single call followed by an assertion on its result"),
+        }
+    }
+    let call = call.ok_or("it does not call a function of the program")?;
+    let expected = expected.ok_or("it does not assert on the result of the call")?;
+    let callee = match &call.func.kind {
+        ExpressionKind::Path(path) => path.as_ident().and_then(|ident| {
+            noir_ast
+                .functions
+                .iter()
+                .find(|function| function.name.name == ident.name && !is_test(function))
+        }),
+        _ => None,
+    }
+    .ok_or("it does not call a function of the program")?;
+    if call.arguments.len() != callee.parameters.len() {
+        return Err("its call has the wrong number of arguments");
+    }
+    let inputs = call
+        .arguments
+        .iter()
+        .zip(&callee.parameters)
+        .map(|(argument, (_, typ, _))| aleo_literal(argument, typ))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("its arguments are not all literals")?;
+    let output = aleo_literal(expected, &callee.return_type)
+        .ok_or("its expected result is not a literal")?;
+    Ok(TestVector {
+        name: test.name.name.clone(),
+        function: callee.name.name.clone(),
+        inputs,
+        outputs: vec![output],
+    })
+}
+
+/// Writes a Noir literal of type `typ` as an Aleo literal.
+fn aleo_literal(expression: &Expression, typ: &UnresolvedType) -> Option<String> {
+    match &expression.kind {
+        ExpressionKind::Literal(Literal::Integer(value, _)) => {
+            Some(format!("{value}{}", to_aleo_type(typ).ok()?))
+        }
+        ExpressionKind::Literal(Literal::Bool(value)) => Some(value.to_string()),
+        _ => None,
+    }
+}
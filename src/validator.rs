@@ -0,0 +1,96 @@
+//! Consistency checks on a compiled program, catching mistakes of the
+//! compiler that snarkVM would otherwise only report when the program runs.
+
+use crate::{
+    error::CompileError,
+    ir::{AleoFunction, AleoProgram, CallSite, Instruction, Item, Signature},
+    CallableKind, Dialect,
+};
+
+/// Checks every `call` of the program against the signature of its callee,
+/// and every assertion against the forms `dialect` has.
+pub fn validate(program: &AleoProgram, dialect: Dialect) -> Result<(), CompileError> {
+    for call_site in &program.call_sites {
+        validate_call(program, call_site).map_err(|error| error.with_span(call_site.span))?;
+    }
+    for item in &program.items {
+        if let Item::Function(function) = item {
+            validate_assertions(function, dialect)?;
+        }
+    }
+    Ok(())
+}
+
+/// Accepts a boolean asserted with `assert.eq rX true` in any dialect, and
+/// with `assert rX` in those that have it.
+fn validate_assertions(function: &AleoFunction, dialect: Dialect) -> Result<(), CompileError> {
+    for instruction in &function.instructions {
+        if let Instruction::AssertTrue { operand } = instruction {
+            if !dialect.has_boolean_assert() {
+                return Err(CompileError::Internal {
+                    rule: "assert-form",
+                    message: format!(
+                        "`{}` asserts `{operand}` with `assert`, which the target has no \
+                         instruction for",
+                        function.name
+                    ),
+                });
+            }
+        }
+    }
+    match &function.finalize {
+        Some((_, block)) => validate_assertions(block, dialect),
+        None => Ok(()),
+    }
+}
+
+fn validate_call(program: &AleoProgram, call_site: &CallSite) -> Result<(), CompileError> {
+    let internal_error = |rule, message| CompileError::Internal { rule, message };
+    let Some(Signature { kind, inputs }) = program.signatures.get(&call_site.callee) else {
+        return Err(internal_error(
+            "call-target",
+            format!(
+                "`{}` calls `{}`, which is not in the program",
+                call_site.caller, call_site.callee
+            ),
+        ));
+    };
+    if call_site.operand_types.len() != inputs.len() {
+        return Err(internal_error(
+            "call-arity",
+            format!(
+                "`{}` calls `{}` with {} operands but it has {} inputs",
+                call_site.caller,
+                call_site.callee,
+                call_site.operand_types.len(),
+                inputs.len()
+            ),
+        ));
+    }
+    for (position, (operand_type, input_type)) in
+        call_site.operand_types.iter().zip(inputs).enumerate()
+    {
+        let (Some(operand_type), Some(input_type)) = (operand_type, input_type) else {
+            continue;
+        };
+        if operand_type != input_type {
+            return Err(internal_error(
+                "call-operand-type",
+                format!(
+                    "`{}` passes a `{operand_type}` as input {position} of `{}`, which is a `{input_type}`",
+                    call_site.caller, call_site.callee
+                ),
+            ));
+        }
+        if *kind == CallableKind::Closure && program.records.contains(operand_type) {
+            return Err(internal_error(
+                "closure-record-input",
+                format!(
+                    "`{}` passes the record `{operand_type}` to the closure `{}`, which cannot take records",
+                    call_site.caller, call_site.callee
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
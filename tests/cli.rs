@@ -0,0 +1,187 @@
+//! What the command line prints on which stream, for scripts wrapping it.
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_noir_to_aleo_instructions"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn build(args: &[&str], output_dir: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_noir_to_aleo_instructions"))
+        .arg("build")
+        .args(args)
+        .arg("--output-dir")
+        .arg(output_dir)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).unwrap()
+}
+
+#[test]
+fn build_writes_the_program_and_prints_its_path() {
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = build(&["tests/add"], output_dir.path());
+    assert!(output.status.success());
+    let path = output_dir.path().join("add.aleo");
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .starts_with("program add.aleo;\n"));
+    assert_eq!(stdout(&output), format!("{}\n", path.display()));
+    assert_eq!(stderr(&output), "Compiling tests/add\n");
+}
+
+#[test]
+fn quiet_build_prints_only_the_written_path() {
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = build(&["tests/add", "--quiet"], output_dir.path());
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        format!("{}\n", output_dir.path().join("add.aleo").display())
+    );
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn print_writes_only_the_program_to_stdout() {
+    let output_dir = tempfile::tempdir().unwrap();
+    for args in [
+        &["tests/add", "--print"][..],
+        &["tests/add", "--print", "-q"],
+    ] {
+        let output = build(args, output_dir.path());
+        assert!(output.status.success());
+        assert!(stdout(&output).starts_with("program add.aleo;\n"));
+    }
+    assert_eq!(fs::read_dir(output_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn verbose_build_writes_the_program_and_prints_it_to_stdout() {
+    let output_dir = tempfile::tempdir().unwrap();
+    for args in [
+        &["tests/add", "--verbose"][..],
+        &["tests/add", "--verbose", "-q"],
+    ] {
+        let output = build(args, output_dir.path());
+        assert!(output.status.success());
+        assert_eq!(
+            stdout(&output),
+            fs::read_to_string(output_dir.path().join("add.aleo")).unwrap()
+        );
+    }
+}
+
+#[test]
+fn emit_ir_writes_only_the_ir_to_stdout() {
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = build(&["tests/add", "--emit-ir"], output_dir.path());
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("AleoProgram {\n    name: \"add\",\n"));
+    assert!(stdout(&output).contains("Function("));
+    assert_eq!(fs::read_dir(output_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn failed_build_reports_errors_on_stderr_even_when_quiet() {
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = build(
+        &["tests/unsupported_for_loop", "--quiet"],
+        output_dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(
+        stderr(&output),
+        "error: tests/unsupported_for_loop/src/main.nr:2:5: for loop expressions are not supported yet\n"
+    );
+}
+
+#[test]
+fn check_prints_ok_and_reports_progress_on_stderr() {
+    let output = run(&["check", "tests/add"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "ok\n");
+    assert_eq!(stderr(&output), "Checking tests/add\n");
+}
+
+#[test]
+fn quiet_check_of_a_supported_package_prints_only_ok() {
+    let output = run(&["check", "tests/add", "--quiet"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "ok\n");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn check_rejects_dynamic_array_indexing() {
+    let output = run(&["check", "tests/dynamic_array_index", "--quiet"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(
+        stderr(&output),
+        "error: tests/dynamic_array_index/src/main.nr:2:5: dynamic array indexing is not supported on Aleo, index with an integer literal\n\
+         error: 1 unsupported construct in 1 function\n"
+    );
+}
+
+#[test]
+fn quiet_check_lists_every_unsupported_construct() {
+    let output = run(&["check", "tests/unsupported_constructs", "--quiet"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(
+        stderr(&output),
+        "error: tests/unsupported_constructs/src/main.nr:3:23: for loop expressions are not supported yet\n\
+         error: tests/unsupported_constructs/src/main.nr:11:5: unknown method `cube`, expected one of: pow, pow_32, invert, square, sqrt, double, abs, wrapping_add, wrapping_sub, wrapping_mul, wrapping_div, wrapping_abs, wrapping_pow\n\
+         error: 2 unsupported constructs in 2 functions\n"
+    );
+}
+
+#[test]
+fn nargo_aleo_builds_the_package_it_runs_in() {
+    let output_dir = tempfile::tempdir().unwrap();
+    // nargo runs `nargo-aleo aleo <args>` in the directory it was run in,
+    // which may be below the package root.
+    let output = Command::new(env!("CARGO_BIN_EXE_nargo-aleo"))
+        .current_dir("tests/add/src")
+        .args(["aleo", "build", "--quiet", "--output-dir"])
+        .arg(output_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    let path = output_dir.path().join("add.aleo");
+    assert_eq!(stdout(&output), format!("{}\n", path.display()));
+    assert!(fs::read_to_string(path)
+        .unwrap()
+        .starts_with("program add.aleo;\n"));
+}
+
+#[test]
+fn nargo_aleo_takes_the_package_from_program_dir() {
+    let output = Command::new(env!("CARGO_BIN_EXE_nargo-aleo"))
+        .args([
+            "aleo",
+            "check",
+            "--program-dir",
+            "tests/unsupported_for_loop",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).ends_with("error: 1 unsupported construct in 1 function\n"));
+}
@@ -0,0 +1,33 @@
+//! The examples, kept working as the documentation of the library API.
+
+use std::{fs, process::Command};
+
+#[test]
+fn compile_api_compiles_and_writes_the_program() {
+    let output_dir = tempfile::tempdir().unwrap();
+    let output = Command::new(env!("CARGO"))
+        .args(["run", "--quiet", "--example", "compile_api", "--"])
+        .arg(output_dir.path())
+        .output()
+        .unwrap();
+    let stdout = std::str::from_utf8(&output.stdout).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "compiled cubes.aleo");
+    assert_eq!(lines[1], "instructions: 3");
+    assert!(lines.iter().any(|line| line.starts_with("compile ")));
+    assert!(stdout.contains("    pow r0 3u32 into r2;\n"));
+    let path = output_dir.path().join("cubes.aleo");
+    assert_eq!(
+        lines.last(),
+        Some(&format!("wrote {}", path.display()).as_str())
+    );
+    assert!(fs::read_to_string(path)
+        .unwrap()
+        .contains("program cubes.aleo;"));
+}
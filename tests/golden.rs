@@ -0,0 +1,103 @@
+//! Compiles every package under `tests/`, that is every directory with a
+//! `Nargo.toml`, and compares the program with its `expected.aleo`, or the
+//! error with its `expected.err` for the packages that must not compile. A
+//! package without the file is a failure. Run with `UPDATE_EXPECT=1` to
+//! write what is compiled now as the expected output instead, which is also
+//! how a new package gets its file.
+
+use std::{env, fs, path::Path};
+
+use noir_to_aleo_instructions::{compile_program, CompileOptions};
+
+const EXPECTED_PROGRAM_FILE: &str = "expected.aleo";
+const EXPECTED_ERROR_FILE: &str = "expected.err";
+
+#[test]
+fn packages_compile_to_their_expected_programs() {
+    let update = env::var_os("UPDATE_EXPECT").is_some_and(|value| value == "1");
+    let mut packages: Vec<_> = fs::read_dir("tests")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.join("Nargo.toml").is_file())
+        .collect();
+    packages.sort();
+    assert!(!packages.is_empty(), "no package under tests/");
+
+    let mut failures = Vec::new();
+    for package in &packages {
+        let (expected_path, stale_path, actual) =
+            match compile_program(package, &CompileOptions::default()) {
+                Ok(program) => (
+                    package.join(EXPECTED_PROGRAM_FILE),
+                    package.join(EXPECTED_ERROR_FILE),
+                    program.aleo_program().to_owned(),
+                ),
+                Err(error) => (
+                    package.join(EXPECTED_ERROR_FILE),
+                    package.join(EXPECTED_PROGRAM_FILE),
+                    format!("{error}\n"),
+                ),
+            };
+        if update {
+            fs::write(&expected_path, &actual).unwrap();
+            if stale_path.is_file() {
+                fs::remove_file(&stale_path).unwrap();
+            }
+            continue;
+        }
+        match fs::read_to_string(&expected_path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(unified_diff(&expected_path, &expected, &actual)),
+            Err(_) if stale_path.is_file() => failures.push(unified_diff(
+                &stale_path,
+                &fs::read_to_string(&stale_path).unwrap(),
+                &actual,
+            )),
+            Err(_) => failures.push(format!("{} is missing\n", expected_path.display())),
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} packages differ from their expected output, \
+         rerun with UPDATE_EXPECT=1 to accept the changes:\n\n{}",
+        failures.len(),
+        packages.len(),
+        failures.join("\n")
+    );
+}
+
+/// The lines of `expected` and `actual`, prefixed with `-` for those only in
+/// `expected`, `+` for those only in `actual` and a space for the others.
+fn unified_diff(expected_path: &Path, expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    // common[i][j] is the length of the longest common subsequence of
+    // expected[i..] and actual[j..].
+    let mut common = vec![vec![0; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut diff = format!("--- {}\n+++ compiled\n", expected_path.display());
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            diff += &format!(" {}\n", expected[i]);
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || common[i + 1][j] >= common[i][j + 1])
+        {
+            diff += &format!("-{}\n", expected[i]);
+            i += 1;
+        } else {
+            diff += &format!("+{}\n", actual[j]);
+            j += 1;
+        }
+    }
+    diff
+}
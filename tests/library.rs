@@ -0,0 +1,142 @@
+//! The library API, as another crate embedding the compiler uses it.
+
+use std::{fs, path::Path};
+
+use noir_to_aleo_instructions::{
+    compile, compile_program, compile_source, compile_to_aleo_instructions, resolve, write,
+    CompileError, CompileOptions, OutputConfig,
+};
+
+#[test]
+fn compile_program_returns_the_program_without_writing_it() {
+    let options = CompileOptions {
+        program_name: Some("adder".to_owned()),
+        ..CompileOptions::default()
+    };
+    let program = compile_program(Path::new("tests/add"), &options).unwrap();
+    assert_eq!(program.program_name(), "adder");
+    assert_eq!(
+        program.aleo_program(),
+        "program adder.aleo;
+
+transition main:
+    input r0 as u32.private;
+    input r1 as u32.private;
+    add r0 r1 into r2;
+    output r2 as u32.private;
+"
+    );
+}
+
+#[test]
+fn compile_to_aleo_instructions_writes_to_the_output_dir() {
+    let output_dir = tempfile::tempdir().unwrap();
+    let output_config = OutputConfig {
+        output_dir: output_dir.path().to_owned(),
+    };
+    let program = compile_to_aleo_instructions(
+        Path::new("tests/add"),
+        &CompileOptions::default(),
+        Some(&output_config),
+    )
+    .unwrap();
+    let written = fs::read_to_string(output_dir.path().join(program.file_name())).unwrap();
+    assert_eq!(written, program.aleo_program());
+}
+
+#[test]
+fn the_stages_can_be_run_one_at_a_time() {
+    let package = resolve(Path::new("tests/add")).unwrap();
+    assert_eq!(package.name(), "add");
+    assert_eq!(package.root_file(), Path::new("tests/add/src/main.nr"));
+
+    let options = CompileOptions {
+        program_name: Some("adder".to_owned()),
+        ..CompileOptions::default()
+    };
+    let program = compile(&package, &options).unwrap();
+    assert_eq!(program.program_name(), "adder");
+
+    let output_dir = tempfile::tempdir().unwrap();
+    let output_config = OutputConfig {
+        output_dir: output_dir.path().to_owned(),
+    };
+    let path = write(&program, &output_config).unwrap();
+    assert_eq!(path, output_dir.path().join("adder.aleo"));
+    assert_eq!(fs::read_to_string(path).unwrap(), program.aleo_program());
+}
+
+#[test]
+fn collected_diagnostics_name_their_function() {
+    let options = CompileOptions {
+        collect_diagnostics: true,
+        ..CompileOptions::default()
+    };
+    let error = compile_program(Path::new("tests/unsupported_constructs"), &options).unwrap_err();
+    let CompileError::Unsupported(diagnostics) = error.without_location() else {
+        panic!("expected collected diagnostics");
+    };
+    let functions: Vec<&str> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.function.as_str())
+        .collect();
+    assert_eq!(functions, ["main", "cubed"]);
+    assert_eq!(
+        diagnostics[0].error,
+        CompileError::UnsupportedExpression("for loop")
+    );
+}
+
+#[test]
+fn compile_source_matches_compiling_the_package() {
+    let source = fs::read_to_string("tests/add/src/main.nr").unwrap();
+    let options = CompileOptions::default();
+    let from_source = compile_source(&source, "add", &options).unwrap();
+    let from_package = compile_program(Path::new("tests/add"), &options).unwrap();
+    assert_eq!(from_source.aleo_program(), from_package.aleo_program());
+}
+
+#[test]
+fn compile_source_locates_errors_in_the_root_file() {
+    let error = compile_source(
+        "fn main(x : u32) -> u32 {\n    for i in 0..2 { x }\n}\n",
+        "looped",
+        &CompileOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "main.nr:2:5: for loop expressions are not supported yet"
+    );
+}
+
+#[test]
+fn token_transfer_writes_the_checked_in_artifacts() {
+    let output_dir = tempfile::tempdir().unwrap();
+    let output_config = OutputConfig {
+        output_dir: output_dir.path().to_owned(),
+    };
+    compile_to_aleo_instructions(
+        Path::new("tests/token_transfer"),
+        &CompileOptions::default(),
+        Some(&output_config),
+    )
+    .unwrap();
+    let expected_dir = Path::new("tests/token_transfer/expected");
+    let mut artifacts: Vec<_> = fs::read_dir(expected_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    artifacts.sort();
+    assert_eq!(
+        artifacts,
+        ["abi.json", "manifest.json", "token_transfer.aleo"]
+    );
+    for artifact in artifacts {
+        assert_eq!(
+            fs::read_to_string(output_dir.path().join(&artifact)).unwrap(),
+            fs::read_to_string(expected_dir.join(&artifact)).unwrap(),
+            "{artifact:?} differs from the checked-in one"
+        );
+    }
+}